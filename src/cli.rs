@@ -1,22 +1,38 @@
-use chrono::Days;
+use crate::dates::{self, parse_date_expression, DateQuery, SearchWindow, WeekStart};
+use crate::error::{self, TwiosError};
+use crate::filters::{self, FilterRule, Pattern};
+use crate::logger::{self, LogFormat};
+use crate::members;
+use crate::people::Person;
+use crate::sources::{Search, SourceConfig};
+use chrono::{DateTime, Days, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use log::warn;
 use regex::Regex;
 use serde;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json;
+use std::collections::HashSet;
 use std::env;
 use std::error::Error;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum CliContext {
     TWIOS,
     COMMENT,
     UTILITY,
+    SERVE,
+    PUBLISH,
+    LINT,
+    BACKFILL,
 }
 
+const DEFAULT_PORT: u16 = 3000;
+
 #[derive(Debug)]
 struct Arg(String, String);
 
@@ -31,6 +47,25 @@ pub struct Args {
     pub comment_body: String,
     pub edit: bool,
     pub dedupe: bool,
+    pub port: u16,
+    pub webhook_secret: String,
+    pub push: bool,
+    pub remote: String,
+    pub pull_request_base: String,
+    pub check: bool,
+    pub online: bool,
+    pub fix: bool,
+    pub backfill: bool,
+    pub from: String,
+    pub to: String,
+    pub force: bool,
+    pub json: bool,
+    pub explain: bool,
+    pub refresh_members: bool,
+    pub api_url: String,
+    pub require_auth: bool,
+    pub verbosity: i8,
+    pub log_format: LogFormat,
 }
 
 #[cfg_attr(test, derive(PartialEq))]
@@ -43,13 +78,13 @@ pub struct LabelConfig {
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FileConfig {
-    labels: Vec<LabelConfig>,
+    pub labels: Vec<LabelConfig>,
     #[serde(default)]
     header: Vec<String>,
     #[serde(default)]
     users: Vec<String>,
     #[serde(default)]
-    exclude: Vec<String>,
+    pub exclude: Vec<String>,
     #[serde(default)]
     exclude_closed_not_merged: bool,
     #[serde(default)]
@@ -58,10 +93,40 @@ pub struct FileConfig {
     last_date: String,
     #[serde(default)]
     query_type: PullRequestQueryType,
+    #[serde(default)]
+    comment_format: CommentFormat,
+    #[serde(default)]
+    week_start: WeekStart,
+    #[serde(default)]
+    pub timezone: String,
+    #[serde(default)]
+    last_run: String,
+    #[serde(default)]
+    sort: Vec<SortRule>,
+    #[serde(default)]
+    pub filters: Vec<FilterRule>,
+    #[serde(default)]
+    pub bots: BotConfig,
+    #[serde(default)]
+    own_organizations: Vec<String>,
+    #[serde(default)]
+    own_organizations_mode: OwnOrganizationsMode,
+    #[serde(default)]
+    ignore_users: Vec<String>,
+    #[serde(default)]
+    members_cache_path: String,
+    #[serde(default)]
+    pub sources: Vec<SourceConfig>,
+    #[serde(default)]
+    people: Vec<Person>,
+    #[serde(default)]
+    github: GitHubConfig,
+    #[serde(default)]
+    data_quality_note: bool,
 }
 
 #[cfg_attr(test, derive(PartialEq))]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AppParams {
     pub exclude_closed_not_merged: bool,
     pub query_type: PullRequestQueryType,
@@ -76,12 +141,45 @@ pub struct AppParams {
     pub context: CliContext,
     pub comment_body: String,
     pub dedupe: bool,
+    pub port: u16,
+    pub webhook_secret: String,
+    pub push: bool,
+    pub remote: String,
+    pub pull_request_base: String,
+    pub comment_format: CommentFormat,
+    pub check: bool,
+    pub online: bool,
+    pub fix: bool,
+    pub timezone: Option<Tz>,
+    pub backfill: bool,
+    pub force: bool,
+    pub week_start: WeekStart,
+    pub json: bool,
+    pub sort: Vec<SortRule>,
+    pub filters: Vec<FilterRule>,
+    pub explain: bool,
+    pub bots: BotConfig,
+    pub own_organizations: Vec<String>,
+    pub own_organizations_mode: OwnOrganizationsMode,
+    pub ignore_users: Vec<String>,
+    pub members_cache_path: String,
+    pub refresh_members: bool,
+    pub sources: Vec<SourceConfig>,
+    pub people: Vec<Person>,
+    pub api_url: String,
+    pub require_auth: bool,
+    pub github: GitHubConfig,
+    pub data_quality_note: bool,
 }
 
+/// Which pull requests make it into a post.
+/// `Both` lists the merged ones and the ones opened in the same period that are still open.
 #[derive(PartialEq, Debug, Clone, Deserialize, Serialize)]
 pub enum PullRequestQueryType {
     Created,
     Merged,
+    #[serde(alias = "Activity")]
+    Both,
 }
 
 impl Default for PullRequestQueryType {
@@ -90,6 +188,130 @@ impl Default for PullRequestQueryType {
     }
 }
 
+/// Layout of the unlabelled repositories in the review comment.
+/// `Text` expects `UNKNOWN` to be replaced by hand, `Checkbox` offers a task-list checkbox per label.
+#[derive(PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub enum CommentFormat {
+    Text,
+    Checkbox,
+}
+
+impl Default for CommentFormat {
+    fn default() -> Self {
+        CommentFormat::Text
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum SortKey {
+    Repository,
+    Merged,
+    Created,
+    Author,
+    Number,
+    /// Added plus deleted lines.
+    Size,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        SortOrder::Ascending
+    }
+}
+
+/// What happens to PRs into repos owned by `own_organizations`, forks included.
+#[derive(PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum OwnOrganizationsMode {
+    Exclude,
+    /// Lists them in a section of their own after the labelled ones.
+    Group,
+}
+
+impl Default for OwnOrganizationsMode {
+    fn default() -> Self {
+        OwnOrganizationsMode::Exclude
+    }
+}
+
+/// Dependabot/Renovate-style PRs. `[bot]` authors are always treated as automated.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BotConfig {
+    /// Regular expressions matched against PR titles ignoring case, e.g. `^chore\(deps\)`.
+    #[serde(default)]
+    pub title_patterns: Vec<String>,
+    /// Treats PRs authored by GitHub App accounts as automated.
+    #[serde(default)]
+    pub apps: bool,
+    /// Lists automated PRs in a collapsed section at the end instead of skipping them.
+    #[serde(default)]
+    pub section: bool,
+    /// `title_patterns` as set by `compile`.
+    #[serde(skip)]
+    pub patterns: Vec<Pattern>,
+}
+
+impl BotConfig {
+    /// Compiles `title_patterns` when the configuration is loaded, the first invalid one is an error.
+    pub fn compile(&self) -> Result<BotConfig, String> {
+        let patterns = self
+            .title_patterns
+            .iter()
+            .enumerate()
+            .map(|(index, pattern)| {
+                Pattern::compile(pattern)
+                    .map_err(|error| format!("bots.title_patterns[{}]: {}", index, error))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(BotConfig {
+            patterns,
+            ..self.clone()
+        })
+    }
+}
+
+/// The GitHub instance to search, github.com unless running against GitHub Enterprise Server.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GitHubConfig {
+    /// REST API root, e.g. `https://github.example.com/api/v3`, `--api-url` takes precedence.
+    #[serde(default)]
+    pub base_url: String,
+    /// File holding a personal access token, read when `GITHUB_PERSONAL_TOKEN` isn't set.
+    #[serde(default)]
+    pub token_file: String,
+    /// Authenticates as a GitHub App installation instead of a user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app: Option<GitHubAppConfig>,
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitHubAppConfig {
+    pub app_id: u64,
+    /// PEM file of a private key generated in the app's settings.
+    pub private_key_path: String,
+    /// Looked up when the app is installed only once.
+    #[serde(default)]
+    pub installation_id: u64,
+}
+
+/// One key of the `sort` option, later keys break ties of earlier ones.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SortRule {
+    pub by: SortKey,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
 impl AppParams {
     pub fn file_name(&self) -> String {
         let mut file_name = self.date.as_ref();
@@ -102,32 +324,169 @@ impl AppParams {
 
         format!("{}{}.md", self.output_path, file_name)
     }
+
+    /// Owners are compared ignoring case, as GitHub does. Only the owner of the repo counts,
+    /// a PR into a fork owned by the organization matches but one into its upstream doesn't.
+    pub fn is_own_organization(&self, organization_name: &str) -> bool {
+        self.own_organizations
+            .iter()
+            .any(|organization| organization.eq_ignore_ascii_case(organization_name))
+    }
+
+    /// The machine-readable counterpart of `file_name`, e.g. `twios/2023-09-18.json`.
+    pub fn json_file_name(&self) -> String {
+        format!("{}.json", self.file_name().trim_end_matches(".md"))
+    }
+
+    /// Value of the `created:`/`merged:` search qualifier for `date` and `date_sign`.
+    pub fn date_qualifier(&self) -> String {
+        match self.date.parse::<DateQuery>() {
+            Ok(date) => date.search_qualifier(&self.date_sign, self.timezone),
+            Err(_) => format!("{}{}", self.date_sign, self.date),
+        }
+    }
+
+    /// `date` as timestamps for sources other than GitHub's search.
+    pub fn search(&self) -> Search {
+        let (after, before) = match self.date.parse::<DateQuery>() {
+            Ok(date) => date.bounds(&self.date_sign, self.timezone),
+            Err(_) => (None, None),
+        };
+
+        Search {
+            query_type: self.query_type.clone(),
+            after,
+            before,
+        }
+    }
+
+    /// `date` as a window to split GitHub's search into, open ends are closed
+    /// by the year GitHub launched and now.
+    pub fn search_window(&self) -> SearchWindow {
+        let search = self.search();
+
+        SearchWindow {
+            after: search.after.unwrap_or_else(|| {
+                Utc.with_ymd_and_hms(2008, 1, 1, 0, 0, 0)
+                    .single()
+                    .unwrap_or_default()
+            }),
+            before: search.before.unwrap_or_else(Utc::now),
+        }
+    }
+
+    /// Whether `timestamp` falls into `date`, on the calendar of `timezone`.
+    pub fn includes(&self, timestamp: DateTime<Utc>) -> bool {
+        let day = match self.timezone {
+            Some(timezone) => timestamp.with_timezone(&timezone).date_naive(),
+            None => timestamp.date_naive(),
+        };
+
+        match (self.date.parse::<DateQuery>(), self.date_sign.as_str()) {
+            (Ok(DateQuery::Day(date)), ">") => day > date,
+            (Ok(DateQuery::Day(date)), "<") => day < date,
+            (Ok(DateQuery::Day(date)), _) => day == date,
+            (Ok(DateQuery::Range(range)), _) => range.start <= day && day <= range.end,
+            (Err(_), _) => true,
+        }
+    }
+
+    /// One set of params per calendar week of `date`, used to backfill missed weeks.
+    pub fn weekly(&self) -> Vec<AppParams> {
+        match self.date.parse::<DateQuery>() {
+            Ok(DateQuery::Range(range)) if self.date_sign.is_empty() => range
+                .weeks(self.week_start)
+                .into_iter()
+                .map(|week| AppParams {
+                    date: week.to_string(),
+                    ..self.clone()
+                })
+                .collect(),
+            _ => vec![self.clone()],
+        }
+    }
+
+    /// Returns params for re-generating a post after `file_config` was updated,
+    /// e.g. from an edited review comment. Same as running with `-edit`.
+    pub fn with_file_config(&self, file_config: &FileConfig) -> error::Result<AppParams> {
+        let (date, date_sign) = split_date_sign(&file_config.last_date, "");
+        Ok(AppParams {
+            labels: file_config.labels.clone(),
+            header: file_config.header.clone(),
+            exclude: file_config.exclude.clone(),
+            users: file_config.users.clone(),
+            exclude_closed_not_merged: file_config.exclude_closed_not_merged,
+            date,
+            date_sign,
+            output_path: file_config.output_path.clone(),
+            query_type: file_config.query_type.clone(),
+            comment_format: file_config.comment_format.clone(),
+            timezone: parse_timezone(&file_config.timezone)?,
+            week_start: file_config.week_start,
+            sort: file_config.sort.clone(),
+            filters: filters::compile_filters(&file_config.filters).map_err(TwiosError::Config)?,
+            bots: file_config.bots.compile().map_err(TwiosError::Config)?,
+            own_organizations: file_config.own_organizations.clone(),
+            own_organizations_mode: file_config.own_organizations_mode,
+            ignore_users: file_config.ignore_users.clone(),
+            members_cache_path: members_cache_path(&file_config.members_cache_path),
+            sources: file_config.sources.clone(),
+            people: file_config.people.clone(),
+            github: file_config.github.clone(),
+            data_quality_note: file_config.data_quality_note,
+            ..self.clone()
+        })
+    }
 }
 
-pub fn args() -> (AppParams, Option<FileConfig>) {
+pub fn args() -> error::Result<(AppParams, Option<FileConfig>)> {
     let args = process_args(read_args());
-
-    let now = chrono::offset::Utc::now();
-    let last_week = chrono::offset::Utc::now()
-        .checked_sub_days(Days::new(7))
-        .unwrap()
-        .naive_utc();
-    let default_date = format!(
-        "{}..{}",
-        last_week.format("%Y-%m-%d"),
-        now.format("%Y-%m-%d")
-    );
+    logger::configure(args.verbosity, args.log_format);
 
     match read_config_from_file(args.config_path.clone()) {
         Ok(file_config) => {
-            let date = if args.date.len() > 0 {
-                args.date
-            } else if args.edit {
-                file_config.last_date.clone()
+            let timezone = match parse_timezone(&file_config.timezone) {
+                Ok(timezone) => timezone,
+                // `config lint` reports an invalid timezone along with every other issue.
+                Err(_) if args.context == CliContext::LINT => None,
+                Err(error) => return Err(error),
+            };
+            let today = today(timezone);
+            let last_run = if file_config.last_run.len() > 0 {
+                &file_config.last_run
+            } else {
+                &file_config.last_date
+            };
+            let (date, date_sign) = if let Some(date) = requested_date(&args, today) {
+                split_date_sign(&date, &args.date_sign)
+            } else if args.edit && !file_config.last_date.trim().is_empty() {
+                split_date_sign(&file_config.last_date, &args.date_sign)
             } else {
-                default_date
+                if !args.backfill {
+                    warn_about_gap(today, last_run);
+                }
+                let (date, date_sign) = continuous_date(today, last_run);
+                let date_sign = if args.date_sign.is_empty() {
+                    date_sign
+                } else {
+                    args.date_sign.clone()
+                };
+                (date, date_sign)
+            };
+            let date = resolve_date(&date, &date_sign, today, file_config.week_start, last_run)?;
+            let filters = match filters::compile_filters(&file_config.filters) {
+                Ok(filters) => filters,
+                // `config lint` reports invalid filters along with every other issue.
+                Err(_) if args.context == CliContext::LINT => vec![],
+                Err(error) => return Err(TwiosError::Config(error)),
             };
-            (
+            let bots = match file_config.bots.compile() {
+                Ok(bots) => bots,
+                // `config lint` reports invalid patterns along with every other issue.
+                Err(_) if args.context == CliContext::LINT => file_config.bots.clone(),
+                Err(error) => return Err(TwiosError::Config(error)),
+            };
+            Ok((
                 AppParams {
                     labels: file_config.labels.clone(),
                     header: file_config.header.clone(),
@@ -135,54 +494,245 @@ pub fn args() -> (AppParams, Option<FileConfig>) {
                     users: file_config.users.clone(),
                     exclude_closed_not_merged: file_config.exclude_closed_not_merged,
                     date,
-                    date_sign: args.date_sign,
+                    date_sign,
                     config_path: args.config_path,
                     output_path: file_config.output_path.clone(),
                     context: args.context,
                     comment_body: args.comment_body,
                     query_type: file_config.query_type.clone(),
                     dedupe: args.dedupe,
+                    port: args.port,
+                    webhook_secret: args.webhook_secret,
+                    push: args.push,
+                    remote: args.remote,
+                    pull_request_base: args.pull_request_base,
+                    comment_format: file_config.comment_format.clone(),
+                    check: args.check,
+                    online: args.online,
+                    fix: args.fix,
+                    timezone,
+                    backfill: args.backfill,
+                    force: args.force,
+                    week_start: file_config.week_start,
+                    json: args.json,
+                    sort: file_config.sort.clone(),
+                    filters,
+                    explain: args.explain,
+                    bots,
+                    own_organizations: file_config.own_organizations.clone(),
+                    own_organizations_mode: file_config.own_organizations_mode,
+                    ignore_users: file_config.ignore_users.clone(),
+                    members_cache_path: members_cache_path(&file_config.members_cache_path),
+                    refresh_members: args.refresh_members,
+                    sources: file_config.sources.clone(),
+                    people: file_config.people.clone(),
+                    api_url: if args.api_url.is_empty() {
+                        file_config.github.base_url.clone()
+                    } else {
+                        args.api_url
+                    },
+                    require_auth: args.require_auth,
+                    github: file_config.github.clone(),
+                    data_quality_note: file_config.data_quality_note,
                 },
                 Some(file_config),
-            )
+            ))
         }
         Err(error) => {
             if args.config_path.len() == 0 {
-                println!("--config-path is not provided.");
-                println!("This will result with unlabelled items.");
-            } else {
-                println!("There was a problem reading your config file.");
-                println!("Check if your config file is correct and valid.");
-                println!("");
-                println!("{:?}", error);
+                warn!("--config-path is not provided, this will result with unlabelled items.");
+            } else if args.context != CliContext::LINT {
+                return Err(config_read_error(&args.config_path, error.as_ref()));
             }
 
-            (
+            let today = today(None);
+            let (date, date_sign) = split_date_sign(
+                &requested_date(&args, today).unwrap_or(default_date(today)),
+                &args.date_sign,
+            );
+            Ok((
                 AppParams {
                     labels: vec![],
                     header: vec![],
                     exclude: vec![],
                     exclude_closed_not_merged: false,
                     users: args.users,
-                    date: if args.date == "" {
-                        default_date
-                    } else {
-                        args.date
-                    },
-                    date_sign: args.date_sign,
+                    date: resolve_date(&date, &date_sign, today, WeekStart::default(), "")?,
+                    date_sign,
                     config_path: args.config_path,
                     output_path: "".to_string(),
                     context: args.context,
                     comment_body: "".to_string(),
                     query_type: PullRequestQueryType::default(),
                     dedupe: args.dedupe,
+                    port: args.port,
+                    webhook_secret: args.webhook_secret,
+                    push: args.push,
+                    remote: args.remote,
+                    pull_request_base: args.pull_request_base,
+                    comment_format: CommentFormat::default(),
+                    check: args.check,
+                    online: args.online,
+                    fix: args.fix,
+                    timezone: None,
+                    backfill: args.backfill,
+                    force: args.force,
+                    week_start: WeekStart::default(),
+                    json: args.json,
+                    sort: vec![],
+                    filters: vec![],
+                    explain: args.explain,
+                    bots: BotConfig::default(),
+                    own_organizations: vec![],
+                    own_organizations_mode: OwnOrganizationsMode::default(),
+                    ignore_users: vec![],
+                    members_cache_path: members_cache_path(""),
+                    refresh_members: args.refresh_members,
+                    sources: vec![],
+                    people: vec![],
+                    api_url: args.api_url,
+                    require_auth: args.require_auth,
+                    github: GitHubConfig::default(),
+                    data_quality_note: false,
                 },
                 None,
-            )
+            ))
         }
     }
 }
 
+/// `--date`, or the range between `--from` and `--to` (today by default).
+fn requested_date(args: &Args, today: NaiveDate) -> Option<String> {
+    if args.from.len() > 0 {
+        let to = if args.to.len() > 0 {
+            args.to.clone()
+        } else {
+            today.to_string()
+        };
+        Some(format!("{}..{}", args.from, to))
+    } else if args.date.len() > 0 {
+        Some(args.date.clone())
+    } else {
+        None
+    }
+}
+
+/// Moves the sign of a date like `>2021-11-28` to `date_sign`, `-before` and `-after` take precedence.
+fn split_date_sign(date: &str, date_sign: &str) -> (String, String) {
+    let (sign, date) = dates::split_sign(date);
+    let sign = if date_sign.is_empty() {
+        sign
+    } else {
+        date_sign
+    };
+    (date.to_string(), sign.to_string())
+}
+
+fn today(timezone: Option<Tz>) -> NaiveDate {
+    let now = chrono::offset::Utc::now();
+    match timezone {
+        Some(timezone) => now.with_timezone(&timezone).date_naive(),
+        None => now.date_naive(),
+    }
+}
+
+/// Starts the day after `last_run` so no day is skipped or counted twice,
+/// falls back to the last 7 days without a previous run.
+/// A run that already reached today continues with everything after it, as `date` and `date_sign`.
+fn continuous_date(today: NaiveDate, last_run: &str) -> (String, String) {
+    match last_run.parse::<DateQuery>() {
+        Ok(last_run) if last_run.end() < today => (
+            format!("{}..{}", last_run.end() + Days::new(1), today),
+            String::from(""),
+        ),
+        Ok(last_run) => (last_run.end().to_string(), String::from(">")),
+        Err(_) => (default_date(today), String::from("")),
+    }
+}
+
+/// Days between the end of `last_run` and `today`, `None` without a previous run.
+fn days_since_last_run(today: NaiveDate, last_run: &str) -> Option<i64> {
+    let last_run = last_run.parse::<DateQuery>().ok()?;
+    Some((today - last_run.end()).num_days())
+}
+
+fn warn_about_gap(today: NaiveDate, last_run: &str) {
+    match days_since_last_run(today, last_run) {
+        Some(days) if days > 7 => warn!(
+            "The previous run ended on {}, {} days ago. Run with -backfill to generate a post for every missed week.",
+            last_run.trim(),
+            days
+        ),
+        _ => (),
+    }
+}
+
+/// Records the end of a published `date` as `last_run`, unless an earlier range was re-generated.
+/// Returns whether `file_config` changed.
+pub fn record_last_run(file_config: &mut FileConfig, date: &str, date_sign: &str) -> bool {
+    let end = match (date.parse::<DateQuery>(), date_sign) {
+        (Ok(date), "") => date.end(),
+        _ => return false,
+    };
+
+    match file_config.last_run.parse::<DateQuery>() {
+        Ok(last_run) if last_run.end() >= end => false,
+        _ => {
+            file_config.last_run = end.to_string();
+            true
+        }
+    }
+}
+
+fn default_date(today: NaiveDate) -> String {
+    let last_week = today.checked_sub_days(Days::new(7)).unwrap_or(today);
+    format!(
+        "{}..{}",
+        last_week.format("%Y-%m-%d"),
+        today.format("%Y-%m-%d")
+    )
+}
+
+fn members_cache_path(path: &str) -> String {
+    if path.is_empty() {
+        members::DEFAULT_CACHE_PATH.to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// An empty `timezone` means UTC, which is how GitHub reads plain dates.
+fn parse_timezone(timezone: &str) -> error::Result<Option<Tz>> {
+    if timezone.is_empty() {
+        return Ok(None);
+    }
+    timezone
+        .parse::<Tz>()
+        .map(Some)
+        .map_err(|error| TwiosError::Config(format!("Invalid timezone {}: {}", timezone, error)))
+}
+
+/// Validates `--date` and normalizes it to `YYYY-MM-DD` or `YYYY-MM-DD..YYYY-MM-DD`.
+fn resolve_date(
+    expression: &str,
+    date_sign: &str,
+    today: NaiveDate,
+    week_start: WeekStart,
+    last_date: &str,
+) -> error::Result<String> {
+    match parse_date_expression(expression, today, week_start, last_date) {
+        Ok(DateQuery::Range(range)) if date_sign.len() > 0 => Err(TwiosError::Config(format!(
+            "-before and -after expect a single date, {} is a range.",
+            range
+        ))),
+        Ok(date) => Ok(date.to_string()),
+        Err(error) => Err(TwiosError::Config(format!(
+            "Invalid --date {}: {}",
+            expression, error
+        ))),
+    }
+}
+
 fn read_args() -> Vec<Arg> {
     let mut args = vec![];
     for pair in env::args().skip(1) {
@@ -211,6 +761,25 @@ fn process_args(pairs: Vec<Arg>) -> Args {
         comment_body: String::from(""),
         edit: false,
         dedupe: false,
+        port: DEFAULT_PORT,
+        webhook_secret: env::var("TWIOS_WEBHOOK_SECRET").unwrap_or_default(),
+        push: false,
+        remote: String::from("origin"),
+        pull_request_base: String::from(""),
+        check: false,
+        online: false,
+        fix: false,
+        backfill: false,
+        from: String::from(""),
+        to: String::from(""),
+        force: false,
+        json: false,
+        explain: false,
+        refresh_members: false,
+        api_url: String::from(""),
+        require_auth: false,
+        verbosity: 0,
+        log_format: LogFormat::Text,
     };
 
     for pair in pairs {
@@ -221,6 +790,20 @@ fn process_args(pairs: Vec<Arg>) -> Args {
             ("utility", _value) => {
                 args.context = CliContext::UTILITY;
             }
+            ("serve", _value) => {
+                args.context = CliContext::SERVE;
+            }
+            ("publish", _value) => {
+                args.context = CliContext::PUBLISH;
+            }
+            ("backfill", _value) => {
+                args.context = CliContext::BACKFILL;
+            }
+            // `config lint`
+            ("config", _value) => {}
+            ("lint", _value) => {
+                args.context = CliContext::LINT;
+            }
             ("--comment", value) => {
                 args.comment_body = value.to_string();
             }
@@ -238,15 +821,56 @@ fn process_args(pairs: Vec<Arg>) -> Args {
             ("-before", _) => args.date_sign = String::from("<"),
             ("-after", _) => args.date_sign = String::from(">"),
             ("-edit", _) => args.edit = true,
+            ("-backfill", _) => args.backfill = true,
+            ("--from", value) => args.from = value.to_string(),
+            ("--to", value) => args.to = value.to_string(),
+            ("-force", _) => args.force = true,
+            ("-json", _) => args.json = true,
+            ("-explain", _) | ("--explain", _) => args.explain = true,
+            ("-refresh-members", _) => args.refresh_members = true,
             ("-dedupe", _) => args.dedupe = true,
+            ("-check", _) | ("--check", _) => args.check = true,
+            ("-online", _) => args.online = true,
+            ("-fix", _) | ("--fix", _) => args.fix = true,
             ("--config-path", value) => args.config_path = value.to_string(),
-            (name, value) => println!("Could not handle argument {} with value {}", name, value),
+            ("--port", value) => match value.parse::<u16>() {
+                Ok(port) => args.port = port,
+                Err(_) => warn!("Could not parse port {}", value),
+            },
+            ("--webhook-secret", value) => args.webhook_secret = value.to_string(),
+            ("-push", _) => args.push = true,
+            ("--remote", value) => args.remote = value.to_string(),
+            ("--api-url", value) => args.api_url = value.to_string(),
+            ("-require-auth", _) | ("--require-auth", _) => args.require_auth = true,
+            ("--pull-request", value) => {
+                args.push = true;
+                args.pull_request_base = if value.is_empty() {
+                    String::from("main")
+                } else {
+                    value.to_string()
+                };
+            }
+            ("-v", _) | ("--verbose", _) => args.verbosity = 1,
+            ("-vv", _) => args.verbosity = 2,
+            ("-q", _) | ("--quiet", _) => args.verbosity = -1,
+            ("--log-format", "json") => args.log_format = LogFormat::Json,
+            ("--log-format", "text") => args.log_format = LogFormat::Text,
+            (name, value) => warn!("Could not handle argument {} with value {}", name, value),
         }
     }
 
     args
 }
 
+/// A config that's given but unreadable stops the run instead of posting unlabelled items,
+/// `config lint` reads it itself to report why.
+fn config_read_error(config_path: &str, error: &dyn Error) -> TwiosError {
+    TwiosError::Config(format!(
+        "Couldn't read {}, check if it is correct and valid: {}",
+        config_path, error
+    ))
+}
+
 fn read_config_from_file<P: AsRef<Path>>(path: P) -> Result<FileConfig, Box<dyn Error>> {
     let file = File::open(path)?;
     let reader = BufReader::new(file);
@@ -322,6 +946,27 @@ impl TwiosCommentOutput {
             file_path: "".to_string(),
         }
     }
+
+    fn assign_label(&mut self, repo: &str, label: &str) {
+        if label == "EXCLUDED" {
+            self.excluded.push(repo.to_string());
+        } else {
+            let mut found_label = false;
+            for config in &mut self.labels {
+                if config.name == label.to_string() {
+                    config.repos.push(repo.to_string());
+                    found_label = true;
+                }
+            }
+            if !found_label && label != "UNKNOWN" {
+                let new_label_config = LabelConfig {
+                    name: label.to_string(),
+                    repos: vec![repo.to_string()],
+                };
+                self.labels.push(new_label_config);
+            }
+        }
+    }
 }
 
 pub fn merge_with_file_config(
@@ -358,12 +1003,73 @@ pub fn merge_with_file_config(
     new_config
 }
 
-pub fn dedupe_file_config(file_config: &mut FileConfig) {
-    for label in file_config.labels.iter_mut() {
-        label.repos.dedup();
+#[derive(Debug, PartialEq)]
+pub struct ConfigConflict {
+    pub repo: String,
+    /// Names of labels claiming the repo, `exclude` when it's excluded as well.
+    pub claimed_by: Vec<String>,
+}
+
+/// Normalizes `file_config` in place:
+/// - labels with the same name (ignoring case) are merged,
+/// - repos are deduplicated ignoring case, keeping the first spelling, and sorted.
+///
+/// Labels without any repos are removed when they're unused, i.e. unless the `Checkbox`
+/// review comment offers them as categories.
+///
+/// Repos that are claimed by several labels, or by a label and `exclude`, are reported
+/// as conflicts as only the first matching label is ever used.
+pub fn normalize_file_config(file_config: &mut FileConfig) -> Vec<ConfigConflict> {
+    let mut labels: Vec<LabelConfig> = vec![];
+    for label in file_config.labels.drain(..) {
+        match labels
+            .iter_mut()
+            .find(|existing| existing.name.to_lowercase() == label.name.to_lowercase())
+        {
+            Some(existing) => existing.repos.extend(label.repos),
+            None => labels.push(label),
+        }
+    }
+
+    for label in labels.iter_mut() {
+        normalize_repos(&mut label.repos);
+    }
+    if file_config.comment_format != CommentFormat::Checkbox {
+        labels.retain(|label| !label.repos.is_empty());
+    }
+    file_config.labels = labels;
+    normalize_repos(&mut file_config.exclude);
+
+    let mut claims: Vec<(String, Vec<String>)> = vec![];
+    let excluded = file_config.exclude.iter().map(|repo| (repo, "exclude"));
+    let labelled = file_config
+        .labels
+        .iter()
+        .flat_map(|label| label.repos.iter().map(|repo| (repo, label.name.as_str())));
+    for (repo, claimed_by) in labelled.chain(excluded) {
+        match claims
+            .iter_mut()
+            .find(|(claimed, _)| claimed.to_lowercase() == repo.to_lowercase())
+        {
+            Some((_, claimed)) => claimed.push(claimed_by.to_string()),
+            None => claims.push((repo.clone(), vec![claimed_by.to_string()])),
+        }
     }
 
-    file_config.exclude.dedup();
+    claims
+        .into_iter()
+        .filter(|(_, claimed_by)| claimed_by.len() > 1)
+        .map(|(repo, claimed_by)| ConfigConflict { repo, claimed_by })
+        .collect()
+}
+
+fn normalize_repos(repos: &mut Vec<String>) {
+    let mut seen = HashSet::new();
+    repos.retain(|repo| seen.insert(repo.trim().to_lowercase()));
+    for repo in repos.iter_mut() {
+        *repo = repo.trim().to_string();
+    }
+    repos.sort_by_key(|repo| repo.to_lowercase());
 }
 
 impl TwiosComment {
@@ -389,28 +1095,26 @@ impl TwiosComment {
                     let re_label =
                         Regex::new(r"\[(?<repo>.*)\]\s+(?<label>(?:\w\s?)+)\s+(?<user>@\w+)")
                             .unwrap();
+                    // Checkbox format: `- [repo] @user` followed by nested `- [x] Label` entries
+                    let re_checkbox_repo = Regex::new(r"^\s*-\s+\[(?<repo>[^\]]+)\]\s+@").unwrap();
+                    let re_checkbox =
+                        Regex::new(r"^\s*-\s+\[(?<checked>[ xX])\]\s+(?<label>.*\S)").unwrap();
+                    let mut checkbox_repo: Option<String> = None;
+
                     for line in value.split("\n") {
-                        for capture in re_label.captures_iter(line) {
-                            let label = &capture["label"];
-                            let repo = &capture["repo"];
-                            if label == "EXCLUDED" {
-                                output.excluded.push(repo.to_string());
-                            } else {
-                                let mut found_label = false;
-                                for config in &mut output.labels {
-                                    if config.name == label.to_string() {
-                                        config.repos.push(repo.to_string());
-                                        found_label = true;
-                                    }
-                                }
-                                if !found_label && label != "UNKNOWN" {
-                                    let new_label_config = LabelConfig {
-                                        name: label.to_string(),
-                                        repos: vec![repo.to_string()],
-                                    };
-                                    output.labels.push(new_label_config);
-                                }
+                        if let Some(capture) = re_checkbox_repo.captures(line) {
+                            checkbox_repo = Some(capture["repo"].to_string());
+                            continue;
+                        }
+                        if let Some(capture) = re_checkbox.captures(line) {
+                            match (&checkbox_repo, &capture["checked"]) {
+                                (Some(_), " ") | (None, _) => {}
+                                (Some(repo), _) => output.assign_label(repo, &capture["label"]),
                             }
+                            continue;
+                        }
+                        for capture in re_label.captures_iter(line) {
+                            output.assign_label(&capture["repo"], &capture["label"]);
                         }
                     }
                 }
@@ -423,7 +1127,7 @@ impl TwiosComment {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use std::vec;
 
     use super::*;
@@ -476,6 +1180,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_reports_unreadable_config() {
+        let error = read_config_from_file("missing.json").unwrap_err();
+
+        match config_read_error("missing.json", error.as_ref()) {
+            TwiosError::Config(message) => {
+                assert!(message.starts_with("Couldn't read missing.json, check if it is correct"))
+            }
+            error => panic!("Expected a config error, got {:?}", error),
+        }
+    }
+
     #[test]
     fn it_processes_config_path_args() {
         assert_eq!(
@@ -488,13 +1204,102 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_processes_serve_args() {
+        let args = process_args(vec![
+            Arg("serve".to_string(), "".to_string()),
+            Arg("--port".to_string(), "8080".to_string()),
+            Arg("--webhook-secret".to_string(), "secret".to_string()),
+        ]);
+        assert_eq!(CliContext::SERVE, args.context);
+        assert_eq!(8080, args.port);
+        assert_eq!("secret", args.webhook_secret);
+    }
+
+    #[test]
+    fn it_processes_publish_args() {
+        let args = process_args(vec![
+            Arg("publish".to_string(), "".to_string()),
+            Arg("--remote".to_string(), "upstream".to_string()),
+            Arg("--pull-request".to_string(), "".to_string()),
+        ]);
+        assert_eq!(CliContext::PUBLISH, args.context);
+        assert_eq!("upstream", args.remote);
+        assert_eq!("main", args.pull_request_base);
+        assert!(args.push);
+    }
+
     #[test]
     fn it_returns_app_params_with_defaults() {
-        let (args, file_config) = args();
+        let (args, file_config) = args().unwrap();
         assert_eq!(CliContext::TWIOS, args.context,);
         assert_eq!(None, file_config,);
     }
 
+    pub(crate) fn app_params_helper() -> AppParams {
+        AppParams {
+            labels: vec![],
+            header: vec![],
+            exclude_closed_not_merged: false,
+            users: vec![],
+            date: "2022-06-30".to_string(),
+            config_path: "".to_string(),
+            context: CliContext::TWIOS,
+            comment_body: "".to_string(),
+            output_path: "".to_string(),
+            date_sign: "".to_string(),
+            exclude: vec![],
+            query_type: PullRequestQueryType::Created,
+            dedupe: false,
+            port: 3000,
+            webhook_secret: "".to_string(),
+            push: false,
+            remote: "origin".to_string(),
+            pull_request_base: "".to_string(),
+            comment_format: CommentFormat::Text,
+            check: false,
+            online: false,
+            fix: false,
+            timezone: None,
+            backfill: false,
+            force: false,
+            week_start: WeekStart::Monday,
+            json: false,
+            sort: vec![],
+            filters: vec![],
+            explain: false,
+            bots: BotConfig::default(),
+            own_organizations: vec![],
+            own_organizations_mode: OwnOrganizationsMode::Exclude,
+            ignore_users: vec![],
+            members_cache_path: ".twios-members.json".to_string(),
+            refresh_members: false,
+            sources: vec![],
+            people: vec![],
+            api_url: String::from(""),
+            require_auth: false,
+            github: GitHubConfig::default(),
+            data_quality_note: false,
+        }
+    }
+
+    #[test]
+    fn it_moves_date_signs() {
+        assert_eq!(
+            ("2021-11-28".to_string(), ">".to_string()),
+            split_date_sign(">2021-11-28", "")
+        );
+        assert_eq!(
+            ("2021-11-28".to_string(), "<".to_string()),
+            split_date_sign(">2021-11-28", "<")
+        );
+
+        let file_config: FileConfig =
+            serde_json::from_str(r#"{"labels": [], "last_date": ">2021-11-28"}"#).unwrap();
+        let edit_params = app_params_helper().with_file_config(&file_config).unwrap();
+        assert_eq!(">2021-11-28", edit_params.date_qualifier());
+    }
+
     #[test]
     fn it_returns_correct_file_name_given_just_date() {
         let app_params = AppParams {
@@ -511,6 +1316,35 @@ mod tests {
             exclude: vec![],
             query_type: PullRequestQueryType::Created,
             dedupe: false,
+            port: 3000,
+            webhook_secret: "".to_string(),
+            push: false,
+            remote: "origin".to_string(),
+            pull_request_base: "".to_string(),
+            comment_format: CommentFormat::Text,
+            check: false,
+            online: false,
+            fix: false,
+            timezone: None,
+            backfill: false,
+            force: false,
+            week_start: WeekStart::Monday,
+            json: false,
+            sort: vec![],
+            filters: vec![],
+            explain: false,
+            bots: BotConfig::default(),
+            own_organizations: vec![],
+            own_organizations_mode: OwnOrganizationsMode::Exclude,
+            ignore_users: vec![],
+            members_cache_path: ".twios-members.json".to_string(),
+            refresh_members: false,
+            sources: vec![],
+            people: vec![],
+            api_url: String::from(""),
+            require_auth: false,
+            github: GitHubConfig::default(),
+            data_quality_note: false,
         };
         assert_eq!("2022-06-30.md", app_params.file_name());
     }
@@ -531,6 +1365,35 @@ mod tests {
             exclude: vec![],
             query_type: PullRequestQueryType::Created,
             dedupe: false,
+            port: 3000,
+            webhook_secret: "".to_string(),
+            push: false,
+            remote: "origin".to_string(),
+            pull_request_base: "".to_string(),
+            comment_format: CommentFormat::Text,
+            check: false,
+            online: false,
+            fix: false,
+            timezone: None,
+            backfill: false,
+            force: false,
+            week_start: WeekStart::Monday,
+            json: false,
+            sort: vec![],
+            filters: vec![],
+            explain: false,
+            bots: BotConfig::default(),
+            own_organizations: vec![],
+            own_organizations_mode: OwnOrganizationsMode::Exclude,
+            ignore_users: vec![],
+            members_cache_path: ".twios-members.json".to_string(),
+            refresh_members: false,
+            sources: vec![],
+            people: vec![],
+            api_url: String::from(""),
+            require_auth: false,
+            github: GitHubConfig::default(),
+            data_quality_note: false,
         };
         assert_eq!("2022-06-30.md", app_params.file_name());
     }
@@ -551,8 +1414,38 @@ mod tests {
             exclude: vec![],
             query_type: PullRequestQueryType::Created,
             dedupe: false,
+            port: 3000,
+            webhook_secret: "".to_string(),
+            push: false,
+            remote: "origin".to_string(),
+            pull_request_base: "".to_string(),
+            comment_format: CommentFormat::Text,
+            check: false,
+            online: false,
+            fix: false,
+            timezone: None,
+            backfill: false,
+            force: false,
+            week_start: WeekStart::Monday,
+            json: false,
+            sort: vec![],
+            filters: vec![],
+            explain: false,
+            bots: BotConfig::default(),
+            own_organizations: vec![],
+            own_organizations_mode: OwnOrganizationsMode::Exclude,
+            ignore_users: vec![],
+            members_cache_path: ".twios-members.json".to_string(),
+            refresh_members: false,
+            sources: vec![],
+            people: vec![],
+            api_url: String::from(""),
+            require_auth: false,
+            github: GitHubConfig::default(),
+            data_quality_note: false,
         };
         assert_eq!("src/twios/2022-06-30.md", app_params.file_name());
+        assert_eq!("src/twios/2022-06-30.json", app_params.json_file_name());
     }
 
     #[test]
@@ -588,6 +1481,123 @@ Available categories
         );
     }
 
+    #[test]
+    fn it_reads_checkbox_issue_comment() {
+        let expected = TwiosComment {
+            body: r#"
+- TWIOS_PATH /twios/
+- TWIOS_DATE 2023-09-11..2023-09-18
+- TWIOS_UNLABELLED
+  - [EmbarkStudios/spdx] @SomeOne
+    - [ ] Ember
+    - [x] Rust
+    - [ ] EXCLUDED
+  - [mainmatter/ember-simple-auth] @SomeTwo @SomeFour
+    - [X] Ember
+    - [ ] Rust
+    - [ ] EXCLUDED
+  - [simplabs/ember-error-route] @SomeThree
+    - [ ] Ember
+    - [ ] Rust
+    - [x] EXCLUDED
+  - [rust-lang/crates.io] @SomeOne
+    - [ ] Ember
+    - [ ] Rust
+    - [ ] EXCLUDED
+
+Check a category to label the repo.
+            "#
+            .to_string(),
+        };
+
+        assert_eq!(
+            TwiosCommentOutput {
+                file_path: "/twios/".to_string(),
+                date: "2023-09-11..2023-09-18".to_string(),
+                excluded: vec!["simplabs/ember-error-route".to_string()],
+                labels: vec![
+                    LabelConfig {
+                        name: "Rust".to_string(),
+                        repos: vec!["EmbarkStudios/spdx".to_string()]
+                    },
+                    LabelConfig {
+                        name: "Ember".to_string(),
+                        repos: vec!["mainmatter/ember-simple-auth".to_string()]
+                    }
+                ],
+            },
+            expected.read()
+        );
+    }
+
+    #[test]
+    fn it_reads_rendered_checkbox_comment() {
+        let app_params = AppParams {
+            labels: vec![
+                LabelConfig {
+                    name: "Ember".to_string(),
+                    repos: vec![],
+                },
+                LabelConfig {
+                    name: "Rust".to_string(),
+                    repos: vec![],
+                },
+            ],
+            output_path: "/twios/".to_string(),
+            date: "2023-09-11..2023-09-18".to_string(),
+            comment_format: CommentFormat::Checkbox,
+            ..app_params_helper()
+        };
+        let mut content = vec![];
+        crate::write_twios_comment_contents(
+            &mut content,
+            &app_params,
+            &crate::tests::items_helper(),
+        );
+
+        let unchecked = TwiosComment {
+            body: content.join("\n"),
+        };
+        assert_eq!(
+            TwiosCommentOutput {
+                file_path: "/twios/".to_string(),
+                date: "2023-09-11..2023-09-18".to_string(),
+                ..TwiosCommentOutput::default()
+            },
+            unchecked.read()
+        );
+
+        let check = |content: &mut Vec<String>, repo: &str, label: &str| {
+            let start = content
+                .iter()
+                .position(|line| line.starts_with(&format!("  - [{}]", repo)))
+                .unwrap();
+            let line = content[start..]
+                .iter()
+                .position(|line| line == &format!("    - [ ] {}", label))
+                .unwrap();
+            content[start + line] = format!("    - [x] {}", label);
+        };
+        check(&mut content, "atom/keyboard-layout", "Rust");
+        check(&mut content, "ember-engines/ember-engines", "EXCLUDED");
+
+        let checked = TwiosComment {
+            body: content.join("\n"),
+        };
+        assert_eq!(
+            TwiosCommentOutput {
+                file_path: "/twios/".to_string(),
+                date: "2023-09-11..2023-09-18".to_string(),
+                excluded: vec!["ember-engines/ember-engines".to_string()],
+                labels: vec![LabelConfig {
+                    name: "Rust".to_string(),
+                    repos: vec!["atom/keyboard-layout".to_string()]
+                }],
+            },
+            checked.read()
+        );
+    }
+
     #[test]
     fn it_merges_with_file_config() {
         let expected = TwiosComment {
@@ -616,6 +1626,21 @@ Available categories
             labels: vec![],
             last_date: "".to_string(),
             query_type: PullRequestQueryType::Created,
+            comment_format: CommentFormat::Text,
+            week_start: WeekStart::Monday,
+            timezone: "".to_string(),
+            last_run: "".to_string(),
+            sort: vec![],
+            filters: vec![],
+            bots: BotConfig::default(),
+            own_organizations: vec![],
+            own_organizations_mode: OwnOrganizationsMode::Exclude,
+            ignore_users: vec![],
+            members_cache_path: "".to_string(),
+            sources: vec![],
+            people: vec![],
+            github: GitHubConfig::default(),
+            data_quality_note: false,
         };
 
         assert_eq!(
@@ -631,6 +1656,21 @@ Available categories
                 }],
                 last_date: ">2021-11-28".to_string(),
                 query_type: PullRequestQueryType::Created,
+                comment_format: CommentFormat::Text,
+                week_start: WeekStart::Monday,
+                timezone: "".to_string(),
+                last_run: "".to_string(),
+                sort: vec![],
+                filters: vec![],
+                bots: BotConfig::default(),
+                own_organizations: vec![],
+                own_organizations_mode: OwnOrganizationsMode::Exclude,
+                ignore_users: vec![],
+                members_cache_path: "".to_string(),
+                sources: vec![],
+                people: vec![],
+                github: GitHubConfig::default(),
+                data_quality_note: false,
             },
             merge_with_file_config(&mut expected.read(), file_config),
         );
@@ -664,6 +1704,21 @@ Available categories
             labels: vec![],
             last_date: "".to_string(),
             query_type: PullRequestQueryType::Created,
+            comment_format: CommentFormat::Text,
+            week_start: WeekStart::Monday,
+            timezone: "".to_string(),
+            last_run: "".to_string(),
+            sort: vec![],
+            filters: vec![],
+            bots: BotConfig::default(),
+            own_organizations: vec![],
+            own_organizations_mode: OwnOrganizationsMode::Exclude,
+            ignore_users: vec![],
+            members_cache_path: "".to_string(),
+            sources: vec![],
+            people: vec![],
+            github: GitHubConfig::default(),
+            data_quality_note: false,
         };
 
         assert_eq!(
@@ -679,6 +1734,21 @@ Available categories
                 }],
                 last_date: ">2021-11-28".to_string(),
                 query_type: PullRequestQueryType::Created,
+                comment_format: CommentFormat::Text,
+                week_start: WeekStart::Monday,
+                timezone: "".to_string(),
+                last_run: "".to_string(),
+                sort: vec![],
+                filters: vec![],
+                bots: BotConfig::default(),
+                own_organizations: vec![],
+                own_organizations_mode: OwnOrganizationsMode::Exclude,
+                ignore_users: vec![],
+                members_cache_path: "".to_string(),
+                sources: vec![],
+                people: vec![],
+                github: GitHubConfig::default(),
+                data_quality_note: false,
             },
             merge_with_file_config(&mut expected.read(), file_config),
         );
@@ -715,6 +1785,21 @@ Available categories
             }],
             last_date: "".to_string(),
             query_type: PullRequestQueryType::Created,
+            comment_format: CommentFormat::Text,
+            week_start: WeekStart::Monday,
+            timezone: "".to_string(),
+            last_run: "".to_string(),
+            sort: vec![],
+            filters: vec![],
+            bots: BotConfig::default(),
+            own_organizations: vec![],
+            own_organizations_mode: OwnOrganizationsMode::Exclude,
+            ignore_users: vec![],
+            members_cache_path: "".to_string(),
+            sources: vec![],
+            people: vec![],
+            github: GitHubConfig::default(),
+            data_quality_note: false,
         };
 
         assert_eq!(
@@ -730,6 +1815,21 @@ Available categories
                 }],
                 last_date: ">2021-11-28".to_string(),
                 query_type: PullRequestQueryType::Created,
+                comment_format: CommentFormat::Text,
+                week_start: WeekStart::Monday,
+                timezone: "".to_string(),
+                last_run: "".to_string(),
+                sort: vec![],
+                filters: vec![],
+                bots: BotConfig::default(),
+                own_organizations: vec![],
+                own_organizations_mode: OwnOrganizationsMode::Exclude,
+                ignore_users: vec![],
+                members_cache_path: "".to_string(),
+                sources: vec![],
+                people: vec![],
+                github: GitHubConfig::default(),
+                data_quality_note: false,
             },
             merge_with_file_config(&mut expected.read(), file_config),
         );
@@ -755,9 +1855,24 @@ Available categories
             }],
             last_date: "".to_string(),
             query_type: PullRequestQueryType::Created,
+            comment_format: CommentFormat::Text,
+            week_start: WeekStart::Monday,
+            timezone: "".to_string(),
+            last_run: "".to_string(),
+            sort: vec![],
+            filters: vec![],
+            bots: BotConfig::default(),
+            own_organizations: vec![],
+            own_organizations_mode: OwnOrganizationsMode::Exclude,
+            ignore_users: vec![],
+            members_cache_path: "".to_string(),
+            sources: vec![],
+            people: vec![],
+            github: GitHubConfig::default(),
+            data_quality_note: false,
         };
 
-        dedupe_file_config(&mut file_config);
+        normalize_file_config(&mut file_config);
 
         assert_eq!(
             FileConfig {
@@ -772,8 +1887,289 @@ Available categories
                 }],
                 last_date: "".to_string(),
                 query_type: PullRequestQueryType::Created,
+                comment_format: CommentFormat::Text,
+                week_start: WeekStart::Monday,
+                timezone: "".to_string(),
+                last_run: "".to_string(),
+                sort: vec![],
+                filters: vec![],
+                bots: BotConfig::default(),
+                own_organizations: vec![],
+                own_organizations_mode: OwnOrganizationsMode::Exclude,
+                ignore_users: vec![],
+                members_cache_path: "".to_string(),
+                sources: vec![],
+                people: vec![],
+                github: GitHubConfig::default(),
+                data_quality_note: false,
+            },
+            file_config,
+        );
+    }
+
+    #[test]
+    fn it_splits_app_params_into_weeks() {
+        let mut app_params = app_params_helper();
+        app_params.date = "2024-03-06..2024-03-17".to_string();
+
+        let weeks = app_params
+            .weekly()
+            .into_iter()
+            .map(|week| (week.date.clone(), week.file_name()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                (
+                    "2024-03-06..2024-03-10".to_string(),
+                    "2024-03-10.md".to_string()
+                ),
+                (
+                    "2024-03-11..2024-03-17".to_string(),
+                    "2024-03-17.md".to_string()
+                ),
+            ],
+            weeks
+        );
+    }
+
+    #[test]
+    fn it_checks_timestamps_against_date() {
+        let mut app_params = app_params_helper();
+        app_params.date = "2024-03-11..2024-03-17".to_string();
+
+        let sunday_night = "2024-03-17T23:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert!(app_params.includes(sunday_night));
+
+        app_params.timezone = Some(chrono_tz::Europe::Berlin);
+        assert!(!app_params.includes(sunday_night));
+        assert!(app_params.includes("2024-03-10T23:30:00Z".parse().unwrap()));
+    }
+
+    #[test]
+    fn it_continues_from_last_run() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 17).unwrap();
+        let date = |date: &str, date_sign: &str| (date.to_string(), date_sign.to_string());
+        assert_eq!(
+            date("2024-03-11..2024-03-17", ""),
+            continuous_date(today, "2024-03-10")
+        );
+        assert_eq!(
+            date("2024-02-26..2024-03-17", ""),
+            continuous_date(today, "2024-02-25")
+        );
+        assert_eq!(
+            date("2024-03-17..2024-03-17", ""),
+            continuous_date(today, "2024-03-04..2024-03-16")
+        );
+        assert_eq!(
+            date("2024-03-10..2024-03-17", ""),
+            continuous_date(today, "")
+        );
+        assert_eq!(
+            date("2024-03-17", ">"),
+            continuous_date(today, "2024-03-17")
+        );
+        assert_eq!(
+            date("2024-03-18", ">"),
+            continuous_date(today, "2024-03-18")
+        );
+    }
+
+    #[test]
+    fn it_counts_days_since_last_run() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 17).unwrap();
+        assert_eq!(Some(7), days_since_last_run(today, "2024-03-10"));
+        assert_eq!(
+            Some(1),
+            days_since_last_run(today, "2024-03-04..2024-03-16")
+        );
+        assert_eq!(Some(0), days_since_last_run(today, "2024-03-17"));
+        assert_eq!(None, days_since_last_run(today, ""));
+    }
+
+    #[test]
+    fn it_matches_own_organizations() {
+        let app_params = AppParams {
+            own_organizations: vec!["mainmatter".to_string()],
+            ..app_params_helper()
+        };
+
+        assert!(app_params.is_own_organization("mainmatter"));
+        assert!(app_params.is_own_organization("Mainmatter"));
+        assert!(!app_params.is_own_organization("simplabs"));
+    }
+
+    #[test]
+    fn it_records_last_run() {
+        let mut file_config: FileConfig = serde_json::from_str(r#"{"labels": []}"#).unwrap();
+
+        assert!(record_last_run(
+            &mut file_config,
+            "2024-03-11..2024-03-17",
+            ""
+        ));
+        assert_eq!("2024-03-17", file_config.last_run);
+
+        assert!(!record_last_run(&mut file_config, "2024-03-01", ""));
+        assert!(!record_last_run(&mut file_config, "2024-03-20", ">"));
+        assert_eq!("2024-03-17", file_config.last_run);
+    }
+
+    #[test]
+    fn it_normalizes_config() {
+        let mut file_config = FileConfig {
+            exclude_closed_not_merged: false,
+            header: vec![],
+            output_path: "".to_string(),
+            exclude: vec![
+                "simplabs/ember-error-route".to_string(),
+                "Turbo87/sentry-conduit".to_string(),
+                "Simplabs/Ember-Error-Route".to_string(),
+            ],
+            users: vec![],
+            labels: vec![
+                LabelConfig {
+                    name: "Rust".to_string(),
+                    repos: vec![
+                        "rust-lang/crates.io".to_string(),
+                        "EmbarkStudios/spdx".to_string(),
+                        "Rust-Lang/crates.io".to_string(),
+                    ],
+                },
+                LabelConfig {
+                    name: "Ruby".to_string(),
+                    repos: vec![],
+                },
+                LabelConfig {
+                    name: "rust".to_string(),
+                    repos: vec!["turbo87/sentry-conduit".to_string()],
+                },
+                LabelConfig {
+                    name: "Ember".to_string(),
+                    repos: vec!["embarkstudios/spdx".to_string()],
+                },
+            ],
+            last_date: "".to_string(),
+            query_type: PullRequestQueryType::Created,
+            comment_format: CommentFormat::Text,
+            week_start: WeekStart::Monday,
+            timezone: "".to_string(),
+            last_run: "".to_string(),
+            sort: vec![],
+            filters: vec![],
+            bots: BotConfig::default(),
+            own_organizations: vec![],
+            own_organizations_mode: OwnOrganizationsMode::Exclude,
+            ignore_users: vec![],
+            members_cache_path: "".to_string(),
+            sources: vec![],
+            people: vec![],
+            github: GitHubConfig::default(),
+            data_quality_note: false,
+        };
+
+        let conflicts = normalize_file_config(&mut file_config);
+
+        assert_eq!(
+            FileConfig {
+                exclude_closed_not_merged: false,
+                header: vec![],
+                output_path: "".to_string(),
+                exclude: vec![
+                    "simplabs/ember-error-route".to_string(),
+                    "Turbo87/sentry-conduit".to_string(),
+                ],
+                users: vec![],
+                labels: vec![
+                    LabelConfig {
+                        name: "Rust".to_string(),
+                        repos: vec![
+                            "EmbarkStudios/spdx".to_string(),
+                            "rust-lang/crates.io".to_string(),
+                            "turbo87/sentry-conduit".to_string(),
+                        ],
+                    },
+                    LabelConfig {
+                        name: "Ember".to_string(),
+                        repos: vec!["embarkstudios/spdx".to_string()],
+                    },
+                ],
+                last_date: "".to_string(),
+                query_type: PullRequestQueryType::Created,
+                comment_format: CommentFormat::Text,
+                week_start: WeekStart::Monday,
+                timezone: "".to_string(),
+                last_run: "".to_string(),
+                sort: vec![],
+                filters: vec![],
+                bots: BotConfig::default(),
+                own_organizations: vec![],
+                own_organizations_mode: OwnOrganizationsMode::Exclude,
+                ignore_users: vec![],
+                members_cache_path: "".to_string(),
+                sources: vec![],
+                people: vec![],
+                github: GitHubConfig::default(),
+                data_quality_note: false,
             },
             file_config,
         );
+        assert_eq!(
+            vec![
+                ConfigConflict {
+                    repo: "EmbarkStudios/spdx".to_string(),
+                    claimed_by: vec!["Rust".to_string(), "Ember".to_string()],
+                },
+                ConfigConflict {
+                    repo: "turbo87/sentry-conduit".to_string(),
+                    claimed_by: vec!["Rust".to_string(), "exclude".to_string()],
+                },
+            ],
+            conflicts
+        );
+    }
+
+    #[test]
+    fn it_compiles_bot_title_patterns() {
+        let bots = BotConfig {
+            title_patterns: vec![r"^chore\(deps\)".to_string(), "^Bump ".to_string()],
+            ..BotConfig::default()
+        };
+        let compiled = bots.compile().unwrap();
+        assert_eq!(2, compiled.patterns.len());
+        assert!(compiled.patterns[1].is_match("bump serde"));
+
+        let bots = BotConfig {
+            title_patterns: vec!["^Bump ".to_string(), "^chore(".to_string()],
+            ..BotConfig::default()
+        };
+        assert!(bots
+            .compile()
+            .unwrap_err()
+            .starts_with("bots.title_patterns[1]: "));
+    }
+
+    #[test]
+    fn it_keeps_empty_labels_offered_by_checkbox_comment() {
+        let config = r#"{"labels": [{"name": "Ruby", "repos": []}, {"name": "Rust", "repos": ["rust-lang/crates.io"]}]}"#;
+        let mut text_config: FileConfig = serde_json::from_str(config).unwrap();
+        let mut checkbox_config = FileConfig {
+            comment_format: CommentFormat::Checkbox,
+            ..text_config.clone()
+        };
+
+        normalize_file_config(&mut text_config);
+        normalize_file_config(&mut checkbox_config);
+
+        let names = |file_config: &FileConfig| {
+            file_config
+                .labels
+                .iter()
+                .map(|label| label.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["Rust"], names(&text_config));
+        assert_eq!(vec!["Ruby", "Rust"], names(&checkbox_config));
     }
 }
@@ -0,0 +1,572 @@
+use chrono::{
+    DateTime, Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+static RE_WEEK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?<year>\d{4})-W(?<week>\d{1,2})$").unwrap());
+static RE_MONTH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?<year>\d{4})-(?<month>\d{2})$").unwrap());
+static RE_QUARTER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?<year>\d{4})-Q(?<quarter>[1-4])$").unwrap());
+
+/// First day of the week used by `this-week`, `last-week` and `YYYY-Www`.
+#[derive(PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum WeekStart {
+    Monday,
+    Sunday,
+}
+
+impl Default for WeekStart {
+    fn default() -> Self {
+        WeekStart::Monday
+    }
+}
+
+impl WeekStart {
+    fn weekday(&self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+/// Inclusive range of days.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Result<Self, DateError> {
+        if start > end {
+            return Err(DateError(format!(
+                "Range start {} is after its end {}",
+                start.format(DATE_FORMAT),
+                end.format(DATE_FORMAT)
+            )));
+        }
+        Ok(DateRange { start, end })
+    }
+
+    /// Splits the range at week boundaries, the first and last week may be shorter.
+    pub fn weeks(&self, week_start: WeekStart) -> Vec<DateRange> {
+        let mut weeks = vec![];
+        let mut start = self.start;
+        while start <= self.end {
+            let end = DateRange::week(start, week_start).end.min(self.end);
+            weeks.push(DateRange { start, end });
+            start = end + Days::new(1);
+        }
+        weeks
+    }
+
+    pub fn week(day: NaiveDate, week_start: WeekStart) -> Self {
+        let start = day - Days::new(days_since(day.weekday(), week_start.weekday()));
+        DateRange {
+            start,
+            end: start + Days::new(6),
+        }
+    }
+}
+
+impl fmt::Display for DateRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}..{}",
+            self.start.format(DATE_FORMAT),
+            self.end.format(DATE_FORMAT)
+        )
+    }
+}
+
+/// A validated `--date`, either a single day used with `-before`/`-after` or a range.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum DateQuery {
+    Day(NaiveDate),
+    Range(DateRange),
+}
+
+impl DateQuery {
+    /// Value for GitHub's `created:`/`merged:` qualifiers.
+    /// GitHub reads plain dates as UTC, so with a `timezone` the boundaries become
+    /// timestamps with offsets covering whole days in that timezone.
+    pub fn search_qualifier(&self, date_sign: &str, timezone: Option<Tz>) -> String {
+        let timezone = match timezone {
+            Some(timezone) => timezone,
+            None => return format!("{}{}", date_sign, self),
+        };
+
+        match (self, date_sign) {
+            (DateQuery::Day(day), ">") => format!(">{}", end_of_day(*day, timezone)),
+            (DateQuery::Day(day), "<") => format!("<{}", start_of_day(*day, timezone)),
+            (DateQuery::Day(day), _) => format!(
+                "{}..{}",
+                start_of_day(*day, timezone),
+                end_of_day(*day, timezone)
+            ),
+            (DateQuery::Range(range), _) => format!(
+                "{}..{}",
+                start_of_day(range.start, timezone),
+                end_of_day(range.end, timezone)
+            ),
+        }
+    }
+
+    /// Half-open `[after, before)` boundaries for APIs that take timestamps instead of
+    /// GitHub's search syntax. `None` means unbounded.
+    pub fn bounds(
+        &self,
+        date_sign: &str,
+        timezone: Option<Tz>,
+    ) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        let start = |day: NaiveDate| Some(utc_start_of_day(day, timezone));
+        let next = |day: NaiveDate| day.succ_opt().map(|day| utc_start_of_day(day, timezone));
+
+        match (self, date_sign) {
+            (DateQuery::Day(day), ">") => (next(*day), None),
+            (DateQuery::Day(day), "<") => (None, start(*day)),
+            (DateQuery::Day(day), _) => (start(*day), next(*day)),
+            (DateQuery::Range(range), _) => (start(range.start), next(range.end)),
+        }
+    }
+
+    /// Last day of a range, or the day itself.
+    pub fn end(&self) -> NaiveDate {
+        match self {
+            DateQuery::Day(day) => *day,
+            DateQuery::Range(range) => range.end,
+        }
+    }
+}
+
+impl fmt::Display for DateQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DateQuery::Day(day) => write!(f, "{}", day.format(DATE_FORMAT)),
+            DateQuery::Range(range) => range.fmt(f),
+        }
+    }
+}
+
+/// Parses the absolute forms: `YYYY-MM-DD` and `YYYY-MM-DD..YYYY-MM-DD`.
+/// A leading `>`, `<` or `=` left over from older `TWIOS_DATE` values is ignored.
+impl FromStr for DateQuery {
+    type Err = DateError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.starts_with(['>', '<', '=']) {
+            return Err(DateError(format!(
+                "{} has a comparison sign, use -before or -after instead",
+                value
+            )));
+        }
+
+        match value.split_once("..") {
+            Some((start, end)) => Ok(DateQuery::Range(DateRange::new(
+                parse_day(start)?,
+                parse_day(end)?,
+            )?)),
+            None => Ok(DateQuery::Day(parse_day(value)?)),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct DateError(pub String);
+
+impl fmt::Display for DateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Resolves a `--date` expression relative to `today`.
+///
+/// Besides the absolute forms accepted by `DateQuery::from_str` it understands
+/// `yesterday`, `this-week`, `last-week`, `since-last-run` (the day after the end of `last_date`),
+/// ISO weeks `2024-W12`, months `2024-03` and quarters `2024-Q1`.
+pub fn parse_date_expression(
+    expression: &str,
+    today: NaiveDate,
+    week_start: WeekStart,
+    last_date: &str,
+) -> Result<DateQuery, DateError> {
+    let expression = expression.trim();
+
+    let range = match expression {
+        "yesterday" => {
+            let yesterday = today - Days::new(1);
+            DateRange::new(yesterday, yesterday)?
+        }
+        "this-week" => DateRange::week(today, week_start),
+        "last-week" => DateRange::week(today - Days::new(7), week_start),
+        "since-last-run" => {
+            if last_date.trim().is_empty() {
+                return Err(DateError(
+                    "since-last-run needs a previous run, `last_date` is empty".to_string(),
+                ));
+            }
+            // A second run on the same day covers today again rather than nothing.
+            let start = (last_date.parse::<DateQuery>()?.end() + Days::new(1)).min(today);
+            DateRange::new(start, today)?
+        }
+        _ => {
+            if let Some(capture) = RE_WEEK.captures(expression) {
+                let year = capture["year"].parse::<i32>().unwrap();
+                let week = capture["week"].parse::<u32>().unwrap();
+                let monday = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+                    .ok_or_else(|| DateError(format!("{} is not a valid ISO week", expression)))?;
+                match week_start {
+                    WeekStart::Monday => DateRange::week(monday, week_start),
+                    WeekStart::Sunday => DateRange::week(monday - Days::new(1), week_start),
+                }
+            } else if let Some(capture) = RE_MONTH.captures(expression) {
+                let year = capture["year"].parse::<i32>().unwrap();
+                let month = capture["month"].parse::<u32>().unwrap();
+                months(year, month, 1)
+                    .ok_or_else(|| DateError(format!("{} is not a valid month", expression)))?
+            } else if let Some(capture) = RE_QUARTER.captures(expression) {
+                let year = capture["year"].parse::<i32>().unwrap();
+                let quarter = capture["quarter"].parse::<u32>().unwrap();
+                months(year, (quarter - 1) * 3 + 1, 3)
+                    .ok_or_else(|| DateError(format!("{} is not a valid quarter", expression)))?
+            } else {
+                return expression.parse::<DateQuery>();
+            }
+        }
+    };
+
+    Ok(DateQuery::Range(range))
+}
+
+/// Splits the `>` or `<` of a date written the way `-after` and `-before` runs used to record
+/// `last_date`, e.g. `>2021-11-28` -> (`>`, `2021-11-28`).
+pub fn split_sign(value: &str) -> (&str, &str) {
+    let value = value.trim();
+    if value.starts_with(['>', '<']) {
+        value.split_at(1)
+    } else {
+        ("", value)
+    }
+}
+
+fn parse_day(value: &str) -> Result<NaiveDate, DateError> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT).map_err(|_| {
+        DateError(format!(
+            "{} is not a valid date, expected YYYY-MM-DD or one of yesterday, this-week, last-week, since-last-run, YYYY-Www, YYYY-MM, YYYY-Qn",
+            value
+        ))
+    })
+}
+
+fn months(year: i32, first_month: u32, count: u32) -> Option<DateRange> {
+    let start = NaiveDate::from_ymd_opt(year, first_month, 1)?;
+    let end = start.checked_add_months(Months::new(count))? - Days::new(1);
+
+    Some(DateRange { start, end })
+}
+
+/// Half-open `[after, before)` span of a search that is split up when GitHub
+/// can't return all of its results at once.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct SearchWindow {
+    pub after: DateTime<Utc>,
+    pub before: DateTime<Utc>,
+}
+
+impl SearchWindow {
+    /// GitHub's `YYYY-MM-DDTHH:MM:SS+00:00..YYYY-MM-DDTHH:MM:SS+00:00`, the end is inclusive there.
+    pub fn search_qualifier(&self) -> String {
+        format!(
+            "{}..{}",
+            self.after.format(TIMESTAMP_FORMAT),
+            (self.before - Duration::seconds(1)).format(TIMESTAMP_FORMAT)
+        )
+    }
+
+    /// Halves at the middle, `None` once the window is too short to split at whole seconds.
+    pub fn split(&self) -> Option<(SearchWindow, SearchWindow)> {
+        let half = Duration::seconds((self.before - self.after).num_seconds() / 2);
+        if half < Duration::seconds(1) {
+            return None;
+        }
+        let middle = self.after + half;
+
+        Some((
+            SearchWindow {
+                after: self.after,
+                before: middle,
+            },
+            SearchWindow {
+                after: middle,
+                before: self.before,
+            },
+        ))
+    }
+}
+
+fn start_of_day(day: NaiveDate, timezone: Tz) -> String {
+    format_timestamp(day.and_hms_opt(0, 0, 0).unwrap(), timezone)
+}
+
+fn end_of_day(day: NaiveDate, timezone: Tz) -> String {
+    format_timestamp(day.and_hms_opt(23, 59, 59).unwrap(), timezone)
+}
+
+fn utc_start_of_day(day: NaiveDate, timezone: Option<Tz>) -> DateTime<Utc> {
+    let local = day.and_hms_opt(0, 0, 0).unwrap();
+    match timezone {
+        Some(timezone) => timezone
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|start| start.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local)),
+        None => Utc.from_utc_datetime(&local),
+    }
+}
+
+fn format_timestamp(local: NaiveDateTime, timezone: Tz) -> String {
+    // Local times skipped by a DST change don't exist, fall back to reading them as UTC
+    timezone
+        .from_local_datetime(&local)
+        .earliest()
+        .unwrap_or_else(|| timezone.from_utc_datetime(&local))
+        .format(TIMESTAMP_FORMAT)
+        .to_string()
+}
+
+fn days_since(weekday: Weekday, week_start: Weekday) -> u64 {
+    (7 + weekday.num_days_from_monday() as u64 - week_start.num_days_from_monday() as u64) % 7
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, DATE_FORMAT).unwrap()
+    }
+
+    fn range(start: &str, end: &str) -> Result<DateQuery, DateError> {
+        Ok(DateQuery::Range(DateRange {
+            start: day(start),
+            end: day(end),
+        }))
+    }
+
+    // Sunday, like the scheduled run
+    fn today() -> NaiveDate {
+        day("2024-03-17")
+    }
+
+    #[test]
+    fn it_parses_absolute_dates() {
+        assert_eq!(
+            Ok(DateQuery::Day(day("2024-03-01"))),
+            parse_date_expression("2024-03-01", today(), WeekStart::Monday, "")
+        );
+        assert_eq!(
+            range("2024-03-01", "2024-03-08"),
+            parse_date_expression("2024-03-01..2024-03-08", today(), WeekStart::Monday, "")
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_dates() {
+        assert!(parse_date_expression("2024-13-01", today(), WeekStart::Monday, "").is_err());
+        assert!(parse_date_expression("last-month", today(), WeekStart::Monday, "").is_err());
+        assert!(parse_date_expression("2024-W54", today(), WeekStart::Monday, "").is_err());
+        assert!(
+            parse_date_expression("2024-03-08..2024-03-01", today(), WeekStart::Monday, "")
+                .is_err()
+        );
+        assert!(parse_date_expression(">2024-03-01", today(), WeekStart::Monday, "").is_err());
+    }
+
+    #[test]
+    fn it_splits_date_signs() {
+        assert_eq!((">", "2021-11-28"), split_sign(">2021-11-28"));
+        assert_eq!(("<", "2021-11-28"), split_sign(" <2021-11-28"));
+        assert_eq!(
+            ("", "2021-11-28..2021-12-05"),
+            split_sign("2021-11-28..2021-12-05")
+        );
+    }
+
+    #[test]
+    fn it_parses_relative_days() {
+        assert_eq!(
+            range("2024-03-16", "2024-03-16"),
+            parse_date_expression("yesterday", today(), WeekStart::Monday, "")
+        );
+        assert_eq!(
+            range("2024-03-11", "2024-03-17"),
+            parse_date_expression("this-week", today(), WeekStart::Monday, "")
+        );
+        assert_eq!(
+            range("2024-03-04", "2024-03-10"),
+            parse_date_expression("last-week", today(), WeekStart::Monday, "")
+        );
+        assert_eq!(
+            range("2024-03-17", "2024-03-23"),
+            parse_date_expression("this-week", today(), WeekStart::Sunday, "")
+        );
+        assert_eq!(
+            range("2024-03-10", "2024-03-16"),
+            parse_date_expression("last-week", today(), WeekStart::Sunday, "")
+        );
+    }
+
+    #[test]
+    fn it_parses_since_last_run() {
+        assert_eq!(
+            range("2024-03-11", "2024-03-17"),
+            parse_date_expression(
+                "since-last-run",
+                today(),
+                WeekStart::Monday,
+                "2024-03-04..2024-03-10"
+            )
+        );
+        assert_eq!(
+            range("2024-03-17", "2024-03-17"),
+            parse_date_expression("since-last-run", today(), WeekStart::Monday, "2024-03-17")
+        );
+        assert!(parse_date_expression("since-last-run", today(), WeekStart::Monday, "").is_err());
+    }
+
+    #[test]
+    fn it_parses_calendar_periods() {
+        assert_eq!(
+            range("2024-03-18", "2024-03-24"),
+            parse_date_expression("2024-W12", today(), WeekStart::Monday, "")
+        );
+        assert_eq!(
+            range("2024-03-17", "2024-03-23"),
+            parse_date_expression("2024-W12", today(), WeekStart::Sunday, "")
+        );
+        assert_eq!(
+            range("2024-02-01", "2024-02-29"),
+            parse_date_expression("2024-02", today(), WeekStart::Monday, "")
+        );
+        assert_eq!(
+            range("2024-10-01", "2024-12-31"),
+            parse_date_expression("2024-Q4", today(), WeekStart::Monday, "")
+        );
+    }
+
+    #[test]
+    fn it_splits_ranges_into_weeks() {
+        let range = DateRange::new(day("2024-03-01"), day("2024-03-19")).unwrap();
+        assert_eq!(
+            vec![
+                DateRange::new(day("2024-03-01"), day("2024-03-03")).unwrap(),
+                DateRange::new(day("2024-03-04"), day("2024-03-10")).unwrap(),
+                DateRange::new(day("2024-03-11"), day("2024-03-17")).unwrap(),
+                DateRange::new(day("2024-03-18"), day("2024-03-19")).unwrap(),
+            ],
+            range.weeks(WeekStart::Monday)
+        );
+        assert_eq!(
+            DateRange::new(day("2024-03-01"), day("2024-03-02")).unwrap(),
+            range.weeks(WeekStart::Sunday)[0]
+        );
+    }
+
+    #[test]
+    fn it_formats_search_qualifiers() {
+        let week = range("2024-03-25", "2024-03-31").unwrap();
+        let berlin = Some(chrono_tz::Europe::Berlin);
+
+        assert_eq!("2024-03-25..2024-03-31", week.search_qualifier("", None));
+        // DST starts on 2024-03-31 in Europe
+        assert_eq!(
+            "2024-03-25T00:00:00+01:00..2024-03-31T23:59:59+02:00",
+            week.search_qualifier("", berlin)
+        );
+        assert_eq!(
+            ">2024-03-01T23:59:59+01:00",
+            DateQuery::Day(day("2024-03-01")).search_qualifier(">", berlin)
+        );
+        assert_eq!(
+            "<2024-03-01T00:00:00+01:00",
+            DateQuery::Day(day("2024-03-01")).search_qualifier("<", berlin)
+        );
+        assert_eq!(
+            ">2024-03-01",
+            DateQuery::Day(day("2024-03-01")).search_qualifier(">", None)
+        );
+    }
+
+    #[test]
+    fn it_returns_bounds() {
+        let week = range("2024-03-25", "2024-03-31").unwrap();
+        let timestamp = |value: &str| Some(value.parse::<DateTime<Utc>>().unwrap());
+
+        assert_eq!(
+            (
+                timestamp("2024-03-25T00:00:00Z"),
+                timestamp("2024-04-01T00:00:00Z")
+            ),
+            week.bounds("", None)
+        );
+        assert_eq!(
+            (
+                timestamp("2024-03-24T23:00:00Z"),
+                timestamp("2024-03-31T22:00:00Z")
+            ),
+            week.bounds("", Some(chrono_tz::Europe::Berlin))
+        );
+        assert_eq!(
+            (timestamp("2024-03-02T00:00:00Z"), None),
+            DateQuery::Day(day("2024-03-01")).bounds(">", None)
+        );
+        assert_eq!(
+            (None, timestamp("2024-03-01T00:00:00Z")),
+            DateQuery::Day(day("2024-03-01")).bounds("<", None)
+        );
+    }
+
+    #[test]
+    fn it_splits_search_windows() {
+        let timestamp = |value: &str| value.parse::<DateTime<Utc>>().unwrap();
+        let week = SearchWindow {
+            after: timestamp("2024-03-25T00:00:00Z"),
+            before: timestamp("2024-04-01T00:00:00Z"),
+        };
+
+        assert_eq!(
+            "2024-03-25T00:00:00+00:00..2024-03-31T23:59:59+00:00",
+            week.search_qualifier()
+        );
+
+        let (first, second) = week.split().unwrap();
+        assert_eq!(
+            "2024-03-25T00:00:00+00:00..2024-03-28T11:59:59+00:00",
+            first.search_qualifier()
+        );
+        assert_eq!(
+            "2024-03-28T12:00:00+00:00..2024-03-31T23:59:59+00:00",
+            second.search_qualifier()
+        );
+
+        let second = SearchWindow {
+            after: timestamp("2024-03-25T00:00:00Z"),
+            before: timestamp("2024-03-25T00:00:01Z"),
+        };
+        assert_eq!(None, second.split());
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+
+/// Why a run stopped, every kind exits with a code of its own.
+/// Exit code 1 stays reserved for checks that found issues, e.g. `config lint`.
+#[derive(Debug)]
+pub enum TwiosError {
+    /// Missing or invalid configuration or arguments.
+    Config(String),
+    /// GitHub or another host couldn't be reached or rejected a request.
+    Network(String),
+    /// GitHub's rate limit is used up, retrying later or authenticating helps.
+    RateLimit(String),
+    /// A response or a value of the input couldn't be read.
+    Parse(String),
+    /// Reading or writing a file failed.
+    Io(String),
+}
+
+pub type Result<T> = std::result::Result<T, TwiosError>;
+
+impl TwiosError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TwiosError::Config(_) => 2,
+            TwiosError::Network(_) => 3,
+            TwiosError::RateLimit(_) => 4,
+            TwiosError::Parse(_) => 5,
+            TwiosError::Io(_) => 6,
+        }
+    }
+}
+
+impl fmt::Display for TwiosError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TwiosError::Config(message) => write!(f, "Configuration error: {}", message),
+            TwiosError::Network(message) => write!(f, "Request failed: {}", message),
+            TwiosError::RateLimit(message) => write!(
+                f,
+                "GitHub rate limit exceeded: {}\nSet GITHUB_PERSONAL_TOKEN or try again later.",
+                message
+            ),
+            TwiosError::Parse(message) => write!(f, "Couldn't parse {}", message),
+            TwiosError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for TwiosError {}
+
+impl From<octocrab::Error> for TwiosError {
+    fn from(error: octocrab::Error) -> Self {
+        match error {
+            octocrab::Error::GitHub { source, .. } if source.message.contains("rate limit") => {
+                TwiosError::RateLimit(source.message)
+            }
+            octocrab::Error::GitHub { source, .. } => TwiosError::Network(source.message),
+            octocrab::Error::Serde { .. } | octocrab::Error::Json { .. } => {
+                TwiosError::Parse(format!("GitHub response: {}", error))
+            }
+            error => TwiosError::Network(error.to_string()),
+        }
+    }
+}
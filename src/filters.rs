@@ -0,0 +1,409 @@
+use crate::Item;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+#[derive(PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum FilterField {
+    Title,
+    Body,
+    /// Matches when any of the labels of the PR matches.
+    Label,
+    Repository,
+    Organization,
+    Author,
+    State,
+    Draft,
+    /// Added plus deleted lines.
+    Size,
+    ChangedFiles,
+    Comments,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum FilterOperator {
+    Equals,
+    NotEquals,
+    StartsWith,
+    EndsWith,
+    Contains,
+    /// `value` is a regular expression, matched ignoring case like every other operator.
+    Matches,
+    LessThan,
+    GreaterThan,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum FilterAction {
+    /// Drops the PRs matching the rule.
+    Exclude,
+    /// Drops the PRs not matching the rule.
+    Include,
+}
+
+impl Default for FilterAction {
+    fn default() -> Self {
+        FilterAction::Exclude
+    }
+}
+
+/// Compiled `value` of a `Matches` rule, compared by its source.
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Pattern {
+    /// Compiles `pattern` ignoring case, like every pattern of the configuration.
+    pub fn compile(pattern: &str) -> Result<Pattern, String> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(Pattern)
+            .map_err(|error| error.to_string())
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.0.is_match(text)
+    }
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FilterRule {
+    pub field: FilterField,
+    pub operator: FilterOperator,
+    pub value: Value,
+    #[serde(default)]
+    pub action: FilterAction,
+    /// Set by `compile`, a `Matches` rule without it never matches.
+    #[serde(skip)]
+    pub pattern: Option<Pattern>,
+}
+
+impl fmt::Display for FilterRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {:?} {:?} {}",
+            self.action, self.field, self.operator, self.value
+        )
+    }
+}
+
+impl FilterRule {
+    /// Checks the rule beyond what the schema catches and compiles the pattern of `Matches`.
+    pub fn compile(&self) -> Result<FilterRule, String> {
+        match (self.operator, &self.value) {
+            (FilterOperator::Matches, Value::String(pattern)) => Ok(FilterRule {
+                pattern: Some(Pattern::compile(pattern)?),
+                ..self.clone()
+            }),
+            (FilterOperator::Matches, value) => Err(format!("{} is not a pattern", value)),
+            (FilterOperator::LessThan | FilterOperator::GreaterThan, value)
+                if !value.is_number() =>
+            {
+                Err(format!("{} is not a number", value))
+            }
+            _ => Ok(self.clone()),
+        }
+    }
+
+    pub fn matches(&self, item: &Item) -> bool {
+        let values = match field_values(item, self.field) {
+            Some(values) => values,
+            None => return false,
+        };
+        match self.operator {
+            FilterOperator::NotEquals => !self.matches_any(&values, FilterOperator::Equals),
+            operator => self.matches_any(&values, operator),
+        }
+    }
+
+    /// A rule on a value that was never fetched, e.g. the size of a GitLab MR, is skipped.
+    pub fn keeps(&self, item: &Item) -> bool {
+        if field_values(item, self.field).is_none() {
+            return true;
+        }
+        match self.action {
+            FilterAction::Exclude => !self.matches(item),
+            FilterAction::Include => self.matches(item),
+        }
+    }
+
+    fn matches_any(&self, values: &[String], operator: FilterOperator) -> bool {
+        let expected = match &self.value {
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        };
+
+        match (operator, &self.pattern) {
+            (FilterOperator::Matches, Some(Pattern(pattern))) => {
+                values.iter().any(|actual| pattern.is_match(actual))
+            }
+            (FilterOperator::Matches, None) => false,
+            _ => values
+                .iter()
+                .any(|actual| compare(actual, operator, &expected)),
+        }
+    }
+}
+
+/// `None` when the value is unknown, e.g. the size of a PR whose details couldn't be fetched.
+fn field_values(item: &Item, field: FilterField) -> Option<Vec<String>> {
+    let values = match field {
+        FilterField::Title => vec![item.issue_title.clone()],
+        FilterField::Body => vec![item.body.clone().unwrap_or_default()],
+        FilterField::Label => item.labels.clone(),
+        FilterField::Repository => vec![item.full_repository_name.clone()],
+        FilterField::Organization => vec![item.organization_name.clone()],
+        FilterField::Author => vec![item.user_login.clone()],
+        FilterField::State => vec![item.state.clone()],
+        FilterField::Draft => vec![item.draft.to_string()],
+        FilterField::Size => vec![item.size()?.to_string()],
+        FilterField::ChangedFiles => vec![item.changed_files?.to_string()],
+        FilterField::Comments => vec![item.comments.to_string()],
+    };
+    Some(values)
+}
+
+/// Compares text ignoring case, `Matches` is handled by the compiled pattern of the rule.
+fn compare(actual: &str, operator: FilterOperator, expected: &str) -> bool {
+    let number = |value: &str| value.parse::<f64>().ok();
+    let (actual_text, expected_text) = (actual.to_lowercase(), expected.to_lowercase());
+
+    match operator {
+        FilterOperator::Equals => actual_text == expected_text,
+        FilterOperator::NotEquals => actual_text != expected_text,
+        FilterOperator::StartsWith => actual_text.starts_with(&expected_text),
+        FilterOperator::EndsWith => actual_text.ends_with(&expected_text),
+        FilterOperator::Contains => actual_text.contains(&expected_text),
+        FilterOperator::Matches => false,
+        FilterOperator::LessThan => match (number(actual), number(expected)) {
+            (Some(actual), Some(expected)) => actual < expected,
+            _ => false,
+        },
+        FilterOperator::GreaterThan => match (number(actual), number(expected)) {
+            (Some(actual), Some(expected)) => actual > expected,
+            _ => false,
+        },
+    }
+}
+
+/// Splits `items` into the kept ones and the dropped ones along with the index of the
+/// first rule that dropped them.
+pub fn apply_filters(items: Vec<Item>, rules: &[FilterRule]) -> (Vec<Item>, Vec<(Item, usize)>) {
+    let mut kept = vec![];
+    let mut dropped = vec![];
+
+    for item in items {
+        match rules.iter().position(|rule| !rule.keeps(&item)) {
+            Some(index) => dropped.push((item, index)),
+            None => kept.push(item),
+        }
+    }
+
+    (kept, dropped)
+}
+
+/// Checks every rule that can be wrong beyond what the schema catches.
+pub fn validate_filters(rules: &[FilterRule]) -> Vec<(usize, String)> {
+    rules
+        .iter()
+        .enumerate()
+        .filter_map(|(index, rule)| rule.compile().err().map(|error| (index, error)))
+        .collect()
+}
+
+/// Compiles every rule when the configuration is loaded, the first invalid one is an error.
+pub fn compile_filters(rules: &[FilterRule]) -> Result<Vec<FilterRule>, String> {
+    rules
+        .iter()
+        .enumerate()
+        .map(|(index, rule)| {
+            rule.compile()
+                .map_err(|error| format!("filters[{}]: {}", index, error))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn item_helper() -> Item {
+        Item {
+            issue_title: "chore(deps): bump serde".to_string(),
+            organization_name: "mainmatter".to_string(),
+            full_repository_name: "mainmatter/ember-simple-auth".to_string(),
+            state: "open".to_string(),
+            labels: vec!["dependencies".to_string(), "javascript".to_string()],
+            draft: true,
+            additions: Some(1),
+            deletions: Some(1),
+            changed_files: Some(1),
+            ..crate::tests::items_helper()[1].clone()
+        }
+    }
+
+    fn rule(field: FilterField, operator: FilterOperator, value: Value) -> FilterRule {
+        let rule = FilterRule {
+            field,
+            operator,
+            value,
+            action: FilterAction::Exclude,
+            pattern: None,
+        };
+        rule.compile().unwrap_or(rule)
+    }
+
+    #[test]
+    fn it_matches_rules() {
+        let item = item_helper();
+
+        assert!(rule(
+            FilterField::Title,
+            FilterOperator::StartsWith,
+            json!("chore(deps)")
+        )
+        .matches(&item));
+        assert!(rule(
+            FilterField::Label,
+            FilterOperator::Equals,
+            json!("dependencies")
+        )
+        .matches(&item));
+        assert!(!rule(
+            FilterField::Label,
+            FilterOperator::NotEquals,
+            json!("javascript")
+        )
+        .matches(&item));
+        assert!(rule(FilterField::Draft, FilterOperator::Equals, json!(true)).matches(&item));
+        assert!(rule(FilterField::Size, FilterOperator::LessThan, json!(3)).matches(&item));
+        assert!(!rule(FilterField::Size, FilterOperator::GreaterThan, json!(3)).matches(&item));
+        assert!(rule(
+            FilterField::Repository,
+            FilterOperator::Matches,
+            json!("^mainmatter/")
+        )
+        .matches(&item));
+    }
+
+    #[test]
+    fn it_ignores_case_for_every_operator() {
+        let item = item_helper();
+
+        for operator in [
+            FilterOperator::Equals,
+            FilterOperator::StartsWith,
+            FilterOperator::EndsWith,
+            FilterOperator::Contains,
+            FilterOperator::Matches,
+        ] {
+            assert!(
+                rule(FilterField::Author, operator, json!("bobrimperator")).matches(&item),
+                "{:?}",
+                operator
+            );
+        }
+    }
+
+    #[test]
+    fn it_skips_rules_on_unknown_values() {
+        let mut item = item_helper();
+        item.additions = None;
+        item.changed_files = None;
+
+        let rules = [
+            rule(FilterField::Size, FilterOperator::LessThan, json!(3)),
+            FilterRule {
+                action: FilterAction::Include,
+                ..rule(FilterField::Size, FilterOperator::GreaterThan, json!(3))
+            },
+            rule(
+                FilterField::ChangedFiles,
+                FilterOperator::NotEquals,
+                json!(5),
+            ),
+        ];
+        for rule in rules.iter() {
+            assert!(!rule.matches(&item), "{}", rule);
+            assert!(rule.keeps(&item), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn it_applies_filters() {
+        let mut other = item_helper();
+        other.issue_number = "43".to_string();
+        other.organization_name = "rust-lang".to_string();
+        other.draft = false;
+
+        let rules = vec![
+            rule(FilterField::Draft, FilterOperator::Equals, json!(true)),
+            FilterRule {
+                action: FilterAction::Include,
+                ..rule(
+                    FilterField::Organization,
+                    FilterOperator::NotEquals,
+                    json!("mainmatter"),
+                )
+            },
+        ];
+
+        let (kept, dropped) = apply_filters(vec![item_helper(), other.clone()], &rules);
+        assert_eq!(vec![other], kept);
+        assert_eq!(vec![(item_helper(), 0)], dropped);
+
+        let mut own = item_helper();
+        own.draft = false;
+        let (kept, dropped) = apply_filters(vec![own.clone()], &rules);
+        assert!(kept.is_empty());
+        assert_eq!(vec![(own, 1)], dropped);
+    }
+
+    #[test]
+    fn it_reads_rules_from_config() {
+        let rule: FilterRule = serde_json::from_value(json!({
+            "field": "Title",
+            "operator": "StartsWith",
+            "value": "chore(deps)"
+        }))
+        .unwrap();
+
+        assert_eq!(FilterAction::Exclude, rule.action);
+        assert_eq!(
+            r#"Exclude Title StartsWith "chore(deps)""#,
+            rule.to_string()
+        );
+    }
+
+    #[test]
+    fn it_validates_filters() {
+        let rules = vec![
+            rule(FilterField::Title, FilterOperator::Matches, json!("chore(")),
+            rule(FilterField::Size, FilterOperator::LessThan, json!("three")),
+            rule(FilterField::Size, FilterOperator::LessThan, json!(3)),
+        ];
+
+        let indices = validate_filters(&rules)
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0, 1], indices);
+
+        assert_eq!(
+            Err("filters[0]: \"three\" is not a number".to_string()),
+            compile_filters(&rules[1..])
+        );
+        let compiled = compile_filters(&rules[2..]).unwrap();
+        assert_eq!(rules[2..].to_vec(), compiled);
+    }
+}
@@ -0,0 +1,272 @@
+use crate::cli::FileConfig;
+use crate::filters::Pattern;
+use chrono_tz::Tz;
+use jsonschema::JSONSchema;
+use octocrab::Octocrab;
+use regex::Regex;
+use serde_json::Value;
+
+const CONFIG_SCHEMA: &str = include_str!("../config.schema.json");
+
+#[derive(Debug, PartialEq)]
+pub struct LintIssue {
+    pub location: String,
+    pub message: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RepoStatus {
+    Found { full_name: String, archived: bool },
+    Missing,
+    Failed(String),
+}
+
+pub fn validate_schema(config: &Value) -> Vec<LintIssue> {
+    let schema: Value = serde_json::from_str(CONFIG_SCHEMA).expect("Config schema is valid JSON");
+    let validator = JSONSchema::compile(&schema).expect("Config schema is a valid schema");
+
+    let issues = match validator.validate(config) {
+        Ok(()) => vec![],
+        Err(errors) => errors
+            .map(|error| LintIssue {
+                location: error.instance_path.to_string(),
+                message: error.to_string(),
+            })
+            .collect(),
+    };
+    issues
+}
+
+/// Every repo mentioned in `labels` and `exclude` along with where it was found.
+pub fn config_repos(file_config: &FileConfig) -> Vec<(String, String)> {
+    let labelled = file_config.labels.iter().flat_map(|label| {
+        label
+            .repos
+            .iter()
+            .map(|repo| (format!("labels.{}", label.name), repo.clone()))
+    });
+    let excluded = file_config
+        .exclude
+        .iter()
+        .map(|repo| ("exclude".to_string(), repo.clone()));
+
+    labelled.chain(excluded).collect()
+}
+
+pub fn check_repo_names(file_config: &FileConfig) -> Vec<LintIssue> {
+    // Owners are alphanumeric with single hyphens, repos may also contain `.` and `_`.
+    let re_repo = Regex::new(r"^[A-Za-z0-9](?:-?[A-Za-z0-9])*/[A-Za-z0-9._-]+$").unwrap();
+    // Other sources prefix their project paths, which can be nested, e.g. `gitlab:GNOME/gtk`.
+    let re_source_repo =
+        Regex::new(r"^[A-Za-z0-9_-]+:[A-Za-z0-9._-]+(?:/[A-Za-z0-9._-]+)+$").unwrap();
+
+    config_repos(file_config)
+        .into_iter()
+        .filter(|(_, repo)| {
+            let is_valid = if is_source_repo(repo) {
+                re_source_repo.is_match(repo)
+            } else {
+                re_repo.is_match(repo)
+            };
+            !is_valid || repo.ends_with("/.") || repo.ends_with("/..")
+        })
+        .map(|(location, repo)| LintIssue {
+            location,
+            message: format!("{} is not a valid owner/repo name", repo),
+        })
+        .collect()
+}
+
+pub fn check_bot_patterns(file_config: &FileConfig) -> Vec<LintIssue> {
+    file_config
+        .bots
+        .title_patterns
+        .iter()
+        .enumerate()
+        .filter_map(|(index, pattern)| {
+            Pattern::compile(pattern).err().map(|message| LintIssue {
+                location: format!("bots.title_patterns.{}", index),
+                message,
+            })
+        })
+        .collect()
+}
+
+pub fn check_timezone(file_config: &FileConfig) -> Vec<LintIssue> {
+    if file_config.timezone.is_empty() {
+        return vec![];
+    }
+    match file_config.timezone.parse::<Tz>() {
+        Ok(_) => vec![],
+        Err(error) => vec![LintIssue {
+            location: "timezone".to_string(),
+            message: error.to_string(),
+        }],
+    }
+}
+
+/// Whether the repo comes from a source other than GitHub, e.g. `gitlab:GNOME/gtk`.
+pub fn is_source_repo(repo: &str) -> bool {
+    repo.contains(':')
+}
+
+/// Looks the repo up on GitHub, which follows renames and transfers to the current name.
+pub async fn resolve_repo(octocrab: &Octocrab, repo: &str) -> RepoStatus {
+    let (owner, name) = match repo.split_once('/') {
+        Some(parts) => parts,
+        None => return RepoStatus::Failed(format!("{} is not a valid owner/repo name", repo)),
+    };
+
+    match octocrab.repos(owner, name).get().await {
+        Ok(repository) => RepoStatus::Found {
+            full_name: repository.full_name.unwrap_or(repo.to_string()),
+            archived: repository.archived.unwrap_or(false),
+        },
+        Err(octocrab::Error::GitHub { source, .. }) if source.message == "Not Found" => {
+            RepoStatus::Missing
+        }
+        Err(error) => RepoStatus::Failed(format!("{:?}", error)),
+    }
+}
+
+/// Replaces every `(from, to)` entry in `labels` and `exclude`.
+pub fn rename_repos(file_config: &mut FileConfig, renames: &[(String, String)]) {
+    let rename = |repo: &mut String| {
+        if let Some((_, to)) = renames.iter().find(|(from, _)| from == repo) {
+            *repo = to.clone();
+        }
+    };
+
+    for label in file_config.labels.iter_mut() {
+        label.repos.iter_mut().for_each(rename);
+    }
+    file_config.exclude.iter_mut().for_each(rename);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn file_config_helper() -> FileConfig {
+        serde_json::from_value(json!({
+            "labels": [
+                { "name": "Rust", "repos": ["rust-lang/crates.io", "rust-lang crates.io"] },
+                { "name": "Ember", "repos": ["simplabs/ember-simple-auth"] },
+                { "name": "GNOME", "repos": ["gitlab:GNOME/gtk", "gitlab:GNOME"] }
+            ],
+            "exclude": ["-simplabs/simplabs.github.io", "simplabs/.."]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_accepts_sample_config() {
+        let config: Value = serde_json::from_str(include_str!("../sample_config.json")).unwrap();
+        assert_eq!(Vec::<LintIssue>::new(), validate_schema(&config));
+    }
+
+    #[test]
+    fn it_accepts_written_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("sample_config.json");
+        let mut file_config: FileConfig =
+            serde_json::from_str(include_str!("../sample_config.json")).unwrap();
+        crate::cli::normalize_file_config(&mut file_config);
+        crate::cli::write_config_to_file(&path, &file_config).unwrap();
+
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(Vec::<LintIssue>::new(), validate_schema(&written));
+    }
+
+    #[test]
+    fn it_reports_schema_violations() {
+        let config = json!({
+            "labels": [{ "name": "Rust", "repos": "rust-lang/crates.io" }],
+            "query_type": "Opened",
+            "exclud": []
+        });
+
+        let locations = validate_schema(&config)
+            .into_iter()
+            .map(|issue| issue.location)
+            .collect::<Vec<_>>();
+
+        assert_eq!(3, locations.len());
+        assert!(locations.contains(&"/labels/0/repos".to_string()));
+        assert!(locations.contains(&"/query_type".to_string()));
+        assert!(locations.contains(&"".to_string()));
+    }
+
+    #[test]
+    fn it_reports_invalid_repo_names() {
+        assert_eq!(
+            vec![
+                LintIssue {
+                    location: "labels.Rust".to_string(),
+                    message: "rust-lang crates.io is not a valid owner/repo name".to_string(),
+                },
+                LintIssue {
+                    location: "labels.GNOME".to_string(),
+                    message: "gitlab:GNOME is not a valid owner/repo name".to_string(),
+                },
+                LintIssue {
+                    location: "exclude".to_string(),
+                    message: "-simplabs/simplabs.github.io is not a valid owner/repo name"
+                        .to_string(),
+                },
+                LintIssue {
+                    location: "exclude".to_string(),
+                    message: "simplabs/.. is not a valid owner/repo name".to_string(),
+                },
+            ],
+            check_repo_names(&file_config_helper())
+        );
+    }
+
+    #[test]
+    fn it_reports_invalid_bot_patterns() {
+        let mut file_config = file_config_helper();
+        file_config.bots.title_patterns =
+            vec!["^chore\\(deps\\)".to_string(), "^chore(".to_string()];
+
+        let locations = check_bot_patterns(&file_config)
+            .into_iter()
+            .map(|issue| issue.location)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["bots.title_patterns.1".to_string()], locations);
+    }
+
+    #[test]
+    fn it_reports_invalid_timezone() {
+        let mut file_config = file_config_helper();
+        assert_eq!(Vec::<LintIssue>::new(), check_timezone(&file_config));
+
+        file_config.timezone = "Europe/Vienna".to_string();
+        assert_eq!(Vec::<LintIssue>::new(), check_timezone(&file_config));
+
+        file_config.timezone = "Mars/Base".to_string();
+        let issues = check_timezone(&file_config);
+        assert_eq!(1, issues.len());
+        assert_eq!("timezone", issues[0].location);
+        assert!(issues[0].message.contains("Mars/Base"));
+    }
+
+    #[test]
+    fn it_renames_repos() {
+        let mut file_config = file_config_helper();
+        rename_repos(
+            &mut file_config,
+            &[(
+                "simplabs/ember-simple-auth".to_string(),
+                "mainmatter/ember-simple-auth".to_string(),
+            )],
+        );
+
+        assert_eq!(
+            vec!["mainmatter/ember-simple-auth".to_string()],
+            file_config.labels[1].repos
+        );
+    }
+}
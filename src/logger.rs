@@ -0,0 +1,108 @@
+use chrono::{SecondsFormat, Utc};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Layout of the diagnostics written to stderr, stdout only carries the requested output.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum LogFormat {
+    Text,
+    /// One JSON object per line, e.g. for log collectors in CI.
+    Json,
+}
+
+struct Logger;
+
+static LOGGER: Logger = Logger;
+static JSON: AtomicBool = AtomicBool::new(false);
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = if JSON.load(Ordering::Relaxed) {
+            format_json(record)
+        } else {
+            format_text(record)
+        };
+        let _ = writeln!(io::stderr(), "{}", line);
+    }
+
+    fn flush(&self) {
+        let _ = io::stderr().flush();
+    }
+}
+
+fn format_text(record: &Record) -> String {
+    match record.level() {
+        Level::Info => record.args().to_string(),
+        level => format!("{}: {}", level.as_str().to_lowercase(), record.args()),
+    }
+}
+
+fn format_json(record: &Record) -> String {
+    serde_json::json!({
+        "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+    })
+    .to_string()
+}
+
+/// `-q` shows warnings and errors only, `-v` adds every request and decision, `-vv` every PR found.
+pub fn level(verbosity: i8) -> LevelFilter {
+    match verbosity {
+        i8::MIN..=-1 => LevelFilter::Warn,
+        0 => LevelFilter::Info,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Logs at `Info` as text until `configure` is called with the parsed arguments.
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
+
+pub fn configure(verbosity: i8, format: LogFormat) {
+    log::set_max_level(level(verbosity));
+    JSON.store(format == LogFormat::Json, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_verbosity() {
+        assert_eq!(LevelFilter::Warn, level(-1));
+        assert_eq!(LevelFilter::Info, level(0));
+        assert_eq!(LevelFilter::Debug, level(1));
+        assert_eq!(LevelFilter::Trace, level(2));
+    }
+
+    #[test]
+    fn it_formats_records() {
+        let args = format_args!("Skipping 2024-03-31.md");
+        let record = Record::builder()
+            .args(args)
+            .level(Level::Warn)
+            .target("this_week_in_open_source")
+            .build();
+
+        assert_eq!("warn: Skipping 2024-03-31.md", format_text(&record));
+
+        let json: serde_json::Value = serde_json::from_str(&format_json(&record)).unwrap();
+        assert_eq!("WARN", json["level"]);
+        assert_eq!("this_week_in_open_source", json["target"]);
+        assert_eq!("Skipping 2024-03-31.md", json["message"]);
+    }
+}
@@ -1,23 +1,50 @@
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, trace, warn};
 use octocrab::{models, Octocrab};
 use serde;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::env;
-use std::fs::File;
+use std::fs;
 use std::io::prelude::*;
-use std::thread::sleep;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 use std::{collections::HashSet, io};
 
+mod auth;
 mod cli;
-use cli::{args, AppParams, PullRequestQueryType};
+mod dates;
+mod error;
+mod filters;
+mod lint;
+mod logger;
+mod members;
+mod people;
+mod publish;
+mod server;
+mod sources;
+use cli::{
+    args, AppParams, BotConfig, CommentFormat, FileConfig, OwnOrganizationsMode,
+    PullRequestQueryType, SortKey, SortOrder, SortRule,
+};
+use dates::SearchWindow;
+use error::TwiosError;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
+const SEARCH_RESULTS_LIMIT: u64 = 1000;
+/// Searches that timed out are repeated this often before their results are taken as they are.
+const SEARCH_RETRIES: u32 = 2;
+/// Halving a week this often ends with windows of about 10 minutes.
+const SEARCH_SPLIT_DEPTH: u32 = 10;
+/// Github API doesn't like requests happening too often.
+const SEARCH_PAUSE: Duration = Duration::from_secs(1);
 
 const BREAK_LINE: &str = r#"
 
 "#;
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 enum ItemMergeStatus {
     Merged,
     NotMerged,
@@ -25,7 +52,7 @@ enum ItemMergeStatus {
 }
 
 #[cfg_attr(test, derive(PartialEq))]
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 struct Item {
     issue_number: String,
     issue_title: String,
@@ -36,8 +63,82 @@ struct Item {
     repository_url: String,
     user_login: String,
     user_url: String,
+    // Name of the person the login belongs to according to `people`.
+    #[serde(default)]
+    user_name: Option<String>,
     state: String, // "open", "closed"
     merge_status: ItemMergeStatus,
+    created_at: DateTime<Utc>,
+    closed_at: Option<DateTime<Utc>>,
+    merged_at: Option<DateTime<Utc>>,
+    labels: Vec<String>,
+    comments: u32,
+    body: Option<String>,
+    // Only known once the pull request itself was fetched.
+    draft: bool,
+    additions: Option<u64>,
+    deletions: Option<u64>,
+    changed_files: Option<u64>,
+    bot: bool,
+}
+
+impl Item {
+    /// Added plus deleted lines, once both are known.
+    fn size(&self) -> Option<u64> {
+        Some(self.additions? + self.deletions?)
+    }
+
+    /// How the author is referenced in the post, the person's name or `@` and the login.
+    fn author(&self) -> String {
+        match &self.user_name {
+            Some(name) => name.clone(),
+            None => format!("@{}", self.user_login),
+        }
+    }
+}
+
+/// What's missing from a run that went through despite failures.
+#[derive(Debug, Default)]
+struct DataQuality {
+    /// `(user, error)` of searches that failed.
+    failed_users: Vec<(String, String)>,
+    /// `(source, error)` of `sources` that couldn't be fetched.
+    failed_sources: Vec<(String, String)>,
+    /// PRs whose details couldn't be fetched, their merge status is `Unknown`.
+    unknown_items: Vec<Item>,
+}
+
+impl DataQuality {
+    fn is_complete(&self) -> bool {
+        self.failed_users.is_empty()
+            && self.failed_sources.is_empty()
+            && self.unknown_items.is_empty()
+    }
+
+    /// One sentence per gap, for the log and the note of the comment.
+    fn gaps(&self) -> Vec<String> {
+        let mut gaps = vec![];
+        for (user, error) in self.failed_users.iter() {
+            gaps.push(format!("Couldn't search the PRs of {}: {}", user, error));
+        }
+        for (source, error) in self.failed_sources.iter() {
+            gaps.push(format!("Couldn't fetch the PRs from {}: {}", source, error));
+        }
+        for item in self.unknown_items.iter() {
+            gaps.push(format!("Merge status of {} is unknown", item.issue_url));
+        }
+        gaps
+    }
+}
+
+/// The fields of the pulls endpoint `octocrab::models::pulls::PullRequest` is missing.
+#[derive(Deserialize)]
+struct PullRequestDetails {
+    merged_at: Option<DateTime<Utc>>,
+    draft: Option<bool>,
+    additions: Option<u64>,
+    deletions: Option<u64>,
+    changed_files: Option<u64>,
 }
 
 #[cfg_attr(test, derive(PartialEq))]
@@ -51,27 +152,27 @@ struct LabelledItem {
 async fn get_prs(
     octocrab: &Octocrab,
     user: &String,
-    date_sign: &String,
-    date: &String,
+    date_qualifier: &String,
     pr_state_query: &str,
 ) -> octocrab::Result<octocrab::Page<models::issues::Issue>, octocrab::Error> {
+    let query = format!(
+        "is:pr author:{} {}:{}",
+        user.as_str(),
+        pr_state_query,
+        date_qualifier.as_str(),
+    );
+    debug!("Searching {}", query);
     octocrab
         .search()
-        .issues_and_pull_requests(&format!(
-            "is:pr author:{} {}:{}{}",
-            user.as_str(),
-            pr_state_query,
-            date_sign.as_str(),
-            date.as_str(),
-        ))
+        .issues_and_pull_requests(&query)
         .send()
         .await
 }
 
-fn format_item(user_login: String, item: &Item) -> String {
+fn format_item(author: String, item: &Item) -> String {
     format!(
-        "- [{}] [#{}]({}) {} ([@{}])",
-        item.full_repository_name, item.issue_number, item.issue_url, item.issue_title, user_login
+        "- [{}] [#{}]({}) {} ([{}])",
+        item.full_repository_name, item.issue_number, item.issue_url, item.issue_title, author
     )
 }
 
@@ -79,90 +180,289 @@ fn format_label(repo: &LabelledItem) -> String {
     format!("## {}", repo.name)
 }
 
-async fn get_user_items(octocrab: &Octocrab, app_params: &AppParams) -> Vec<Item> {
+/// Searches the PRs of every user, a user whose search fails is skipped and returned with the error.
+async fn get_user_items(
+    octocrab: &Octocrab,
+    app_params: &AppParams,
+) -> error::Result<(Vec<Item>, Vec<(String, String)>)> {
     let mut items: Vec<Item> = vec![];
+    let mut failed_users: Vec<(String, String)> = vec![];
 
-    let query_type = match app_params.query_type {
-        cli::PullRequestQueryType::Merged => "merged",
-        cli::PullRequestQueryType::Created => "created",
+    let query_types = match app_params.query_type {
+        PullRequestQueryType::Merged => vec!["merged"],
+        PullRequestQueryType::Created => vec!["created"],
+        PullRequestQueryType::Both => vec!["merged", "created"],
     };
+    let users = members::resolve_users(
+        octocrab,
+        &app_params.users,
+        &app_params.ignore_users,
+        &app_params.members_cache_path,
+        app_params.refresh_members,
+    )
+    .await;
 
-    for user in app_params.users.clone() {
-        let mut page = get_prs(
-            &octocrab,
-            &user,
-            &app_params.date_sign,
-            &app_params.date,
-            query_type,
-        )
-        .await
-        .unwrap();
+    for user in users {
+        let result = get_items_of_user(octocrab, app_params, &user, &query_types, &mut items).await;
+        match result {
+            Ok(()) => (),
+            // Every following search would fail the same way.
+            Err(error @ TwiosError::RateLimit(_)) => return Err(error),
+            Err(error) => failed_users.push((user.clone(), error.to_string())),
+        }
+
+        tokio::time::sleep(SEARCH_PAUSE).await;
+    }
+
+    Ok((items, failed_users))
+}
+
+/// Adds the PRs of `user` to `items`. PRs found before a failing page are kept.
+async fn get_items_of_user(
+    octocrab: &Octocrab,
+    app_params: &AppParams,
+    user: &String,
+    query_types: &[&str],
+    items: &mut Vec<Item>,
+) -> error::Result<()> {
+    for query_type in query_types.iter() {
+        // The whole period first, then halves of it for as long as results are truncated.
+        let mut searches: Vec<(String, Option<SearchWindow>, u32)> =
+            vec![(app_params.date_qualifier(), None, 0)];
+
+        while let Some((date_qualifier, window, depth)) = searches.pop() {
+            let mut page = get_prs(octocrab, user, &date_qualifier, query_type).await?;
+            for _ in 0..SEARCH_RETRIES {
+                if page.incomplete_results != Some(true) {
+                    break;
+                }
+                debug!(
+                    "Search for PRs of {} {}:{} timed out, retrying",
+                    user, query_type, date_qualifier
+                );
+                tokio::time::sleep(SEARCH_PAUSE).await;
+                page = get_prs(octocrab, user, &date_qualifier, query_type).await?;
+            }
+            if page.incomplete_results == Some(true) {
+                warn!(
+                    "Search for PRs of {} {}:{} timed out, some PRs may be missing.",
+                    user, query_type, date_qualifier
+                );
+            }
 
-        loop {
-            for issue in &page {
-                let url = issue.html_url.to_string();
-                let mut repository_url_parts = url.split("/").collect::<Vec<&str>>();
-                let path_parts = issue
-                    .html_url
-                    .path()
-                    .split("/")
-                    .filter(|x| x.len() > 0)
-                    .collect::<Vec<&str>>();
-
-                repository_url_parts.pop(); // id
-                repository_url_parts.pop(); // /pulls
-
-                let merge_status = if app_params.query_type == PullRequestQueryType::Merged {
-                    ItemMergeStatus::Merged
+            if is_truncated(page.total_count) {
+                debug!(
+                    "Search for PRs of {} {}:{} is truncated",
+                    user, query_type, date_qualifier
+                );
+                let halves = if depth < SEARCH_SPLIT_DEPTH {
+                    window.unwrap_or_else(|| app_params.search_window()).split()
                 } else {
-                    ItemMergeStatus::Unknown
+                    None
                 };
-
-                items.push(Item {
-                    user_login: issue.user.login.clone(),
-                    user_url: issue.user.html_url.to_string(),
-                    issue_number: issue.number.to_string(),
-                    issue_title: issue.title.clone(),
-                    issue_url: url.to_string(),
-                    organization_name: path_parts[0].to_string(),
-                    repository_name: path_parts[1].to_string(),
-                    full_repository_name: format!("{}/{}", path_parts[0], path_parts[1]),
-                    repository_url: repository_url_parts.join("/"),
-                    state: issue.state.clone(),
-                    merge_status,
-                });
+                match halves {
+                    Some((first, second)) => {
+                        searches.push((second.search_qualifier(), Some(second), depth + 1));
+                        searches.push((first.search_qualifier(), Some(first), depth + 1));
+                        tokio::time::sleep(SEARCH_PAUSE).await;
+                        continue;
+                    }
+                    None => warn!(
+                        "Search for PRs of {} {}:{} is truncated, some PRs are missing.",
+                        user, query_type, date_qualifier
+                    ),
+                }
             }
-            page = match octocrab.get_page(&page.next).await.unwrap() {
-                Some(next_page) => next_page,
-                None => {
-                    break;
+
+            loop {
+                for issue in &page {
+                    let url = issue.html_url.to_string();
+                    // PRs both opened and merged in the period show up in both searches.
+                    if items.iter().any(|item| item.issue_url == url) {
+                        continue;
+                    }
+                    trace!("Found {}", url);
+                    let bot = is_automated(
+                        &app_params.bots,
+                        &issue.user.login,
+                        &issue.user.r#type,
+                        &issue.title,
+                    );
+                    if bot && !app_params.bots.section {
+                        debug!("Skipped {} of bot {}", url, issue.user.login);
+                        continue;
+                    }
+                    let (organization_name, repository_name, repository_url) =
+                        match repository(&url) {
+                            Some(repository) => repository,
+                            None => {
+                                warn!("Could not read the repository of {}", url);
+                                continue;
+                            }
+                        };
+
+                    let merge_status = if *query_type == "merged" {
+                        ItemMergeStatus::Merged
+                    } else {
+                        ItemMergeStatus::Unknown
+                    };
+
+                    items.push(Item {
+                        user_login: issue.user.login.clone(),
+                        user_url: issue.user.html_url.to_string(),
+                        user_name: None,
+                        issue_number: issue.number.to_string(),
+                        issue_title: issue.title.clone(),
+                        issue_url: url.to_string(),
+                        full_repository_name: format!("{}/{}", organization_name, repository_name),
+                        organization_name,
+                        repository_name,
+                        repository_url,
+                        state: issue.state.clone(),
+                        merge_status,
+                        created_at: issue.created_at,
+                        closed_at: issue.closed_at,
+                        merged_at: None,
+                        labels: issue
+                            .labels
+                            .iter()
+                            .map(|label| label.name.clone())
+                            .collect(),
+                        comments: issue.comments,
+                        body: issue.body.clone(),
+                        draft: false,
+                        additions: None,
+                        deletions: None,
+                        changed_files: None,
+                        bot,
+                    });
+                }
+                if let Some(next) = &page.next {
+                    debug!("Fetching {}", next);
+                }
+                page = match octocrab.get_page(&page.next).await? {
+                    Some(next_page) => next_page,
+                    None => {
+                        break;
+                    }
                 }
             }
         }
+    }
+
+    Ok(())
+}
 
-        // Github API doesn't like requests happening too often.
-        // We add a timeout here to help with hitting rate limit
-        sleep(Duration::from_secs(1));
+/// Whether `item` is in an `exclude`d repo, or owned by `own_organizations` in `Exclude` mode.
+fn is_excluded(app_params: &AppParams, item: &Item) -> bool {
+    if app_params.exclude.contains(&item.full_repository_name) {
+        debug!(
+            "Excluded {} of {}",
+            item.issue_url, item.full_repository_name
+        );
+        return true;
+    }
+    if app_params.own_organizations_mode == OwnOrganizationsMode::Exclude
+        && app_params.is_own_organization(&item.organization_name)
+    {
+        debug!("Excluded {} of own organization", item.issue_url);
+        return true;
     }
+    false
+}
 
-    items
+/// Pull requests from every configured source besides GitHub.
+/// A source that can't be reached is skipped and returned with the error, so the post still gets written.
+async fn fetch_source_items(app_params: &AppParams) -> (Vec<Item>, Vec<(String, String)>) {
+    let search = app_params.search();
+    let mut items = vec![];
+    let mut failed_sources = vec![];
+
+    for source in app_params.sources.iter() {
+        match source.fetch_items(&search).await {
+            Ok(source_items) => items.extend(
+                source_items
+                    .into_iter()
+                    .filter(|item| !is_excluded(app_params, item)),
+            ),
+            Err(error) => failed_sources.push((source.name(), error.to_string())),
+        }
+    }
+
+    (items, failed_sources)
+}
+
+/// `https://github.example.com/mainmatter/twios/pull/1`
+/// -> (`mainmatter`, `twios`, `https://github.example.com/mainmatter/twios`)
+/// Works for any host, GitHub Enterprise Server serves repos from the root like github.com.
+fn repository(html_url: &str) -> Option<(String, String, String)> {
+    let (scheme, rest) = html_url.split_once("://")?;
+    let (host, path) = rest.split_once('/')?;
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+    let (owner, name) = (segments.next()?, segments.next()?);
+
+    Some((
+        owner.to_string(),
+        name.to_string(),
+        format!("{}://{}/{}/{}", scheme, host, owner, name),
+    ))
 }
 
-async fn set_item_merge_status(octocrab: &Octocrab, items: &mut Vec<Item>) -> () {
+/// The search API returns 1000 results at most.
+fn is_truncated(total_count: Option<u64>) -> bool {
+    total_count.is_some_and(|total_count| total_count > SEARCH_RESULTS_LIMIT)
+}
+
+/// `[bot]` accounts are always automated, GitHub App accounts and titles only when configured.
+fn is_automated(bots: &BotConfig, login: &str, user_type: &str, title: &str) -> bool {
+    login.ends_with("[bot]")
+        || (bots.apps && user_type == "Bot")
+        || bots.patterns.iter().any(|pattern| pattern.is_match(title))
+}
+
+/// PRs found by the `merged` search are known to be merged even without their details.
+fn mark_merge_status_unknown(item: &mut Item) {
+    if item.merge_status != ItemMergeStatus::Merged {
+        item.merge_status = ItemMergeStatus::Unknown;
+    }
+}
+
+/// Completes the search results with the merge status and details only the pulls endpoint has.
+/// The merge status of a PR that can't be fetched becomes `Unknown` unless it's known to be merged.
+async fn set_pull_request_details(octocrab: &Octocrab, items: &mut Vec<Item>) -> () {
     for item in items {
+        let number = match item.issue_number.parse::<u64>() {
+            Ok(number) => number,
+            Err(_) => {
+                mark_merge_status_unknown(item);
+                continue;
+            }
+        };
+        debug!("Fetching details of {}", item.issue_url);
+        let route = format!(
+            "repos/{}/{}/pulls/{}",
+            item.organization_name, item.repository_name, number
+        );
         match octocrab
-            .pulls(item.organization_name.clone(), item.repository_name.clone())
-            .is_merged(item.issue_number.parse::<u64>().unwrap())
+            .get::<PullRequestDetails, _, _>(route, None::<&()>)
             .await
         {
-            Ok(is_merged) => {
-                if is_merged {
+            Ok(pull_request) => {
+                if pull_request.merged_at.is_some() {
                     item.merge_status = ItemMergeStatus::Merged
                 } else {
                     item.merge_status = ItemMergeStatus::NotMerged
                 }
+                item.merged_at = pull_request.merged_at;
+                item.draft = pull_request.draft.unwrap_or(false);
+                item.additions = pull_request.additions;
+                item.deletions = pull_request.deletions;
+                item.changed_files = pull_request.changed_files;
+            }
+            Err(error) => {
+                debug!("Couldn't fetch details of {}: {}", item.issue_url, error);
+                mark_merge_status_unknown(item);
             }
-            Err(_) => item.merge_status = ItemMergeStatus::Unknown,
         }
     }
 }
@@ -172,6 +472,7 @@ fn filter_items_by_merge_status(items: Vec<Item>) -> Vec<Item> {
         .into_iter()
         .filter(|item| {
             if item.merge_status == ItemMergeStatus::NotMerged && item.state == "closed" {
+                debug!("Excluded {}, it was closed without merging", item.issue_url);
                 false
             } else {
                 true
@@ -185,7 +486,7 @@ fn extract_definitions(items: &Vec<Item>) -> Vec<String> {
     let mut unique_repositories = HashSet::new();
 
     for item in items {
-        unique_users.insert(format!("[@{}]: {}", item.user_login, item.user_url));
+        unique_users.insert(format!("[{}]: {}", item.author(), item.user_url));
         unique_repositories.insert(format!(
             "[{}]: {}",
             item.full_repository_name, item.repository_url
@@ -206,12 +507,50 @@ fn extract_definitions(items: &Vec<Item>) -> Vec<String> {
     definitions
 }
 
-async fn initialize_octocrab() -> octocrab::Result<Octocrab> {
-    match env::vars().find(|(key, _)| key == "GITHUB_PERSONAL_TOKEN") {
-        Some((_key, token)) => Octocrab::builder().personal_token(token).build(),
+/// `api_url` points at the REST API of a GitHub Enterprise Server, api.github.com when empty.
+fn build_octocrab(api_url: &str, token: Option<String>) -> octocrab::Result<Octocrab> {
+    let builder = if api_url.is_empty() {
+        Octocrab::builder()
+    } else {
+        // Routes are joined onto the url, without the trailing slash `/api/v3` would be replaced.
+        Octocrab::builder().base_url(format!("{}/", api_url.trim_end_matches('/')))?
+    };
+
+    match token {
+        Some(token) => builder.personal_token(token).build(),
+        None => builder.build(),
+    }
+}
+
+/// Authenticates as the configured GitHub App, with the first token `auth::find_token` finds otherwise.
+async fn initialize_octocrab(app_params: &AppParams) -> error::Result<Octocrab> {
+    let api_url = &app_params.api_url;
+
+    if let Some(app) = &app_params.github.app {
+        let token = match auth::app_jwt(app, Utc::now()) {
+            Ok(jwt) => {
+                let octocrab = build_octocrab(api_url, Some(jwt))?;
+                auth::installation_token(&octocrab, app).await
+            }
+            Err(error) => Err(error.into()),
+        };
+        match token {
+            Ok(token) => return Ok(build_octocrab(api_url, Some(token))?),
+            Err(error) => warn!(
+                "Couldn't authenticate as GitHub App {}: {}",
+                app.app_id, error
+            ),
+        }
+    }
+
+    match auth::find_token(&app_params.github, api_url) {
+        Some(token) => Ok(build_octocrab(api_url, Some(token))?),
+        None if app_params.require_auth => Err(TwiosError::Config(String::from(
+            "No GitHub credentials found, set GITHUB_PERSONAL_TOKEN, github.token_file or github.app.",
+        ))),
         None => {
-            println!("GITHUB_PERSONAL_TOKEN was not provided.");
-            Octocrab::builder().build()
+            warn!("GITHUB_PERSONAL_TOKEN was not provided.");
+            Ok(build_octocrab(api_url, None)?)
         }
     }
 }
@@ -241,14 +580,54 @@ fn match_items_with_labels<'a>(
 fn format_items(items: &Vec<Item>) -> Vec<String> {
     items
         .into_iter()
-        .map(|item| format_item(item.user_login.clone(), &item))
+        .map(|item| format_item(item.author(), &item))
         .collect::<Vec<String>>()
 }
 
+/// Whether a PR was merged in the period or opened in it and still waiting for review.
+/// PRs that were closed without being merged belong to neither.
+fn is_activity(item: &Item) -> bool {
+    item.merge_status == ItemMergeStatus::Merged || item.state == "open"
+}
+
+fn format_activity_items(items: &Vec<Item>) -> Vec<String> {
+    let (merged, opened): (Vec<Item>, Vec<Item>) = items
+        .iter()
+        .filter(|item| is_activity(item))
+        .cloned()
+        .partition(|item| item.merge_status == ItemMergeStatus::Merged);
+
+    let mut content: Vec<String> = vec![];
+    for (title, section_items) in [
+        ("### Merged this week", merged),
+        ("### Opened this week (still open)", opened),
+    ] {
+        if section_items.len() == 0 {
+            continue;
+        }
+        if content.len() > 0 {
+            content.push(String::from(""));
+        }
+        content.push(String::from(title));
+        content.push(String::from(""));
+        content.append(&mut format_items(&section_items));
+    }
+
+    content
+}
+
+fn format_section_items(items: &Vec<Item>, query_type: &PullRequestQueryType) -> Vec<String> {
+    match query_type {
+        PullRequestQueryType::Both => format_activity_items(items),
+        _ => format_items(items),
+    }
+}
+
 fn write_twios_file_contents(
     content: &mut Vec<String>,
     labels: &Vec<LabelledItem>,
     unknown_items: &Vec<Item>,
+    query_type: &PullRequestQueryType,
 ) {
     for (i, label) in labels.iter().filter(|i| i.items.len() > 0).enumerate() {
         if i > 0 {
@@ -256,14 +635,60 @@ fn write_twios_file_contents(
         }
         content.push(format_label(&label));
         content.push(String::from(""));
-        content.append(&mut format_items(&label.items));
+        content.append(&mut format_section_items(&label.items, query_type));
     }
 
     if unknown_items.len() > 0 {
         content.push(String::from(""));
         content.push(String::from("## Unknown"));
         content.push(String::from(""));
-        content.append(&mut format_items(unknown_items));
+        content.append(&mut format_section_items(unknown_items, query_type));
+    }
+}
+
+fn write_own_organizations_contents(
+    content: &mut Vec<String>,
+    own_items: &Vec<Item>,
+    query_type: &PullRequestQueryType,
+) {
+    if own_items.len() == 0 {
+        return;
+    }
+
+    content.push(String::from(""));
+    content.push(String::from("## Own organizations"));
+    content.push(String::from(""));
+    content.append(&mut format_section_items(own_items, query_type));
+}
+
+fn write_bots_contents(content: &mut Vec<String>, bot_items: &Vec<Item>) {
+    if bot_items.len() == 0 {
+        return;
+    }
+
+    content.push(String::from(""));
+    content.push(format!(
+        "<details><summary>Automated PRs ({})</summary>",
+        bot_items.len()
+    ));
+    content.push(String::from(""));
+    content.append(&mut format_items(bot_items));
+    content.push(String::from(""));
+    content.push(String::from("</details>"));
+}
+
+/// Lists what's missing from the post, nothing when the run went through without failures.
+/// Users aren't mentioned with `@` to not notify them from the review comment.
+fn write_data_quality_contents(content: &mut Vec<String>, data_quality: &DataQuality) {
+    if data_quality.is_complete() {
+        return;
+    }
+
+    content.push(String::from(""));
+    content.push(String::from("### Data quality"));
+    content.push(String::from(""));
+    for gap in data_quality.gaps() {
+        content.push(format!("- {}", gap));
     }
 }
 
@@ -278,39 +703,180 @@ fn write_twios_comment_contents(
     content.push(format!("- TWIOS_DATE {}", app_params.date));
     content.push("- TWIOS_UNLABELLED".to_string());
 
-    let mut unknown_labels = HashSet::new();
-    for item in unknown_items.iter() {
-        let label = format!(
-            "  - [{}] UNKNOWN @{}",
-            item.full_repository_name, item.user_login
-        );
-        if !unknown_labels.contains(&label) {
-            unknown_labels.insert(label.clone());
-            content.push(label);
+    match app_params.comment_format {
+        CommentFormat::Text => {
+            let mut unknown_labels = HashSet::new();
+            for item in unknown_items.iter() {
+                let label = format!(
+                    "  - [{}] UNKNOWN @{}",
+                    item.full_repository_name, item.user_login
+                );
+                if !unknown_labels.contains(&label) {
+                    unknown_labels.insert(label.clone());
+                    content.push(label);
+                }
+            }
+
+            content.push("".to_string());
+            content.push("Change repo category to `EXCLUDED` in order to permantently ignore it from TWIOS from now on.".to_string());
         }
-    }
+        CommentFormat::Checkbox => {
+            let mut unknown_repos: Vec<(String, Vec<String>)> = vec![];
+            for item in unknown_items.iter() {
+                let user = format!("@{}", item.user_login);
+                match unknown_repos
+                    .iter_mut()
+                    .find(|(repo, _)| repo == &item.full_repository_name)
+                {
+                    Some((_, users)) => {
+                        if !users.contains(&user) {
+                            users.push(user);
+                        }
+                    }
+                    None => unknown_repos.push((item.full_repository_name.clone(), vec![user])),
+                }
+            }
+
+            for (repo, users) in unknown_repos {
+                content.push(format!("  - [{}] {}", repo, users.join(" ")));
+                for label in app_params.labels.iter() {
+                    content.push(format!("    - [ ] {}", label.name));
+                }
+                content.push("    - [ ] EXCLUDED".to_string());
+            }
 
-    content.push("".to_string());
-    content.push("Change repo category to `EXCLUDED` in order to permantently ignore it from TWIOS from now on.".to_string());
+            content.push("".to_string());
+            content.push("Check a category to label the repo, check `EXCLUDED` in order to permanently ignore it from TWIOS from now on.".to_string());
+        }
+    }
 }
 
-async fn fetch_data(
-    app_params: &AppParams,
-) -> octocrab::Result<(Vec<LabelledItem>, Vec<Item>, Vec<String>)> {
-    let octocrab = initialize_octocrab().await?;
-    let mut items = get_user_items(&octocrab, &app_params).await;
-    items = items
-        .into_iter()
-        .filter(|item| !app_params.exclude.contains(&item.full_repository_name))
-        .collect::<Vec<_>>();
-    set_item_merge_status(&octocrab, &mut items).await;
+async fn fetch_items(app_params: &AppParams) -> error::Result<(Vec<Item>, DataQuality)> {
+    let octocrab = initialize_octocrab(app_params).await?;
+    let (mut items, failed_users) = get_user_items(&octocrab, &app_params).await?;
+    // Before fetching details, so excluded PRs don't cost a request each.
+    items.retain(|item| !is_excluded(app_params, item));
+    set_pull_request_details(&octocrab, &mut items).await;
+    let (mut source_items, failed_sources) = fetch_source_items(app_params).await;
+    items.append(&mut source_items);
+    let (mut items, dropped) = filters::apply_filters(items, &app_params.filters);
+    // `-explain` shows the decisions without `-v`.
+    let explain_level = if app_params.explain {
+        log::Level::Info
+    } else {
+        log::Level::Debug
+    };
+    for (item, index) in dropped.iter() {
+        log::log!(
+            explain_level,
+            "Filtered out {} ({}) by filters[{}]: {}",
+            item.issue_url,
+            item.issue_title,
+            index,
+            app_params.filters[*index]
+        );
+    }
     if app_params.exclude_closed_not_merged
         && app_params.query_type.ne(&PullRequestQueryType::Merged)
     {
         items = filter_items_by_merge_status(items);
     }
-    items.sort_by_key(|item| item.full_repository_name.clone());
+    if app_params.query_type == PullRequestQueryType::Both {
+        items.retain(is_activity);
+    }
+    let mut items = people::credit_people(&items, &app_params.people);
+    sort_items(&mut items, &app_params.sort);
+
+    let data_quality = DataQuality {
+        failed_users,
+        failed_sources,
+        unknown_items: items
+            .iter()
+            .filter(|item| item.merge_status == ItemMergeStatus::Unknown)
+            .cloned()
+            .collect(),
+    };
+    for gap in data_quality.gaps() {
+        warn!("{}", gap);
+    }
+
+    Ok((items, data_quality))
+}
+
+fn compare_items(a: &Item, b: &Item, key: SortKey) -> Ordering {
+    match key {
+        SortKey::Repository => a.full_repository_name.cmp(&b.full_repository_name),
+        SortKey::Merged => a.merged_at.cmp(&b.merged_at),
+        SortKey::Created => a.created_at.cmp(&b.created_at),
+        SortKey::Author => {
+            let author = |item: &Item| {
+                item.user_name
+                    .as_ref()
+                    .unwrap_or(&item.user_login)
+                    .to_lowercase()
+            };
+            author(a).cmp(&author(b))
+        }
+        SortKey::Number => {
+            let number = |item: &Item| item.issue_number.parse::<u64>().unwrap_or(0);
+            number(a).cmp(&number(b))
+        }
+        SortKey::Size => a.size().cmp(&b.size()),
+    }
+}
+
+/// Unmerged PRs and PRs of unknown size come last in either order.
+fn is_sortable(item: &Item, key: SortKey) -> bool {
+    match key {
+        SortKey::Merged => item.merged_at.is_some(),
+        SortKey::Size => item.size().is_some(),
+        _ => true,
+    }
+}
+
+/// Sorts by every rule in turn, by repository when there are none.
+/// Labelled sections and the Unknown section keep this order.
+fn sort_items(items: &mut Vec<Item>, rules: &Vec<SortRule>) {
+    let default_rules = vec![SortRule {
+        by: SortKey::Repository,
+        order: SortOrder::Ascending,
+    }];
+    let rules = if rules.len() > 0 {
+        rules
+    } else {
+        &default_rules
+    };
+
+    items.sort_by(|a, b| {
+        rules
+            .iter()
+            .map(
+                |rule| match (is_sortable(a, rule.by), is_sortable(b, rule.by)) {
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    _ => match rule.order {
+                        SortOrder::Ascending => compare_items(a, b, rule.by),
+                        SortOrder::Descending => compare_items(a, b, rule.by).reverse(),
+                    },
+                },
+            )
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+fn label_items(
+    app_params: &AppParams,
+    items: &Vec<Item>,
+) -> (Vec<LabelledItem>, Vec<Item>, Vec<String>) {
     let markdown_definitions = extract_definitions(&items);
+    // Automated PRs and grouped PRs into own organizations get their own section
+    // and are never up for labelling.
+    let items = items
+        .iter()
+        .filter(|item| !item.bot && !app_params.is_own_organization(&item.organization_name))
+        .cloned()
+        .collect::<Vec<_>>();
 
     let mut labelled_items = app_params
         .labels
@@ -324,61 +890,522 @@ async fn fetch_data(
         .collect::<Vec<LabelledItem>>();
     let (labels, unknown_items) = match_items_with_labels(&mut labelled_items, &items);
 
-    Ok((labels.clone().to_vec(), unknown_items, markdown_definitions))
+    (labels.clone().to_vec(), unknown_items, markdown_definitions)
+}
+
+async fn fetch_data(
+    app_params: &AppParams,
+) -> error::Result<(Vec<LabelledItem>, Vec<Item>, Vec<String>, DataQuality)> {
+    let (items, data_quality) = fetch_items(app_params).await?;
+    let (labels, unknown_items, markdown_definitions) = label_items(app_params, &items);
+    Ok((labels, unknown_items, markdown_definitions, data_quality))
+}
+
+fn write_file(path: &str, contents: &[u8]) -> error::Result<()> {
+    fs::write(path, contents)
+        .map_err(|error| TwiosError::Io(format!("Couldn't write {}: {}", path, error)))
+}
+
+fn write_config(app_params: &AppParams, file_config: &FileConfig) -> error::Result<()> {
+    cli::write_config_to_file(app_params.config_path.clone(), file_config).map_err(|error| {
+        TwiosError::Io(format!(
+            "Couldn't write {}: {}",
+            app_params.config_path, error
+        ))
+    })
+}
+
+fn require_file_config(file_config: Option<FileConfig>) -> error::Result<FileConfig> {
+    file_config.ok_or_else(|| TwiosError::Config("Configuration file doesn't exist".to_string()))
+}
+
+fn write_items_file(app_params: &AppParams, items: &Vec<Item>) -> error::Result<Vec<Item>> {
+    let (labels, unknown_items, markdown_definitions) = label_items(app_params, items);
+    let mut file_content: Vec<String> = vec![];
+    write_twios_file_contents(
+        &mut file_content,
+        &labels,
+        &unknown_items,
+        &app_params.query_type,
+    );
+    write_own_organizations_contents(
+        &mut file_content,
+        &items
+            .iter()
+            .filter(|item| !item.bot && app_params.is_own_organization(&item.organization_name))
+            .cloned()
+            .collect(),
+        &app_params.query_type,
+    );
+    write_bots_contents(
+        &mut file_content,
+        &items.iter().filter(|item| item.bot).cloned().collect(),
+    );
+
+    let contents = [
+        app_params.header.join("\n"),
+        file_content.join("\n"),
+        BREAK_LINE.to_string(),
+        markdown_definitions.join("\n"),
+    ]
+    .concat();
+    write_file(&app_params.file_name(), contents.as_bytes())?;
+
+    if app_params.json {
+        let json = serde_json::to_vec_pretty(items)
+            .map_err(|error| TwiosError::Parse(format!("pull requests as JSON: {}", error)))?;
+        write_file(&app_params.json_file_name(), &json)?;
+    }
+
+    Ok(unknown_items)
+}
+
+async fn write_twios_file(app_params: &AppParams) -> error::Result<(Vec<Item>, DataQuality)> {
+    let (items, data_quality) = fetch_items(app_params).await?;
+    Ok((write_items_file(app_params, &items)?, data_quality))
+}
+
+/// The time that decides which week an item belongs to.
+fn item_timestamp(item: &Item, query_type: &PullRequestQueryType) -> DateTime<Utc> {
+    match query_type {
+        PullRequestQueryType::Merged => {
+            item.merged_at.or(item.closed_at).unwrap_or(item.created_at)
+        }
+        PullRequestQueryType::Created => item.created_at,
+        PullRequestQueryType::Both => match item.merge_status {
+            ItemMergeStatus::Merged => item.merged_at.or(item.closed_at).unwrap_or(item.created_at),
+            _ => item.created_at,
+        },
+    }
+}
+
+/// Writes a post for every week of `date`. Pull requests are searched once for the
+/// whole period and then split into weeks. Existing posts are kept unless `force` is set.
+async fn backfill(
+    app_params: &AppParams,
+    force: bool,
+    file_config: &mut Option<FileConfig>,
+) -> error::Result<()> {
+    let weeks = app_params
+        .weekly()
+        .into_iter()
+        .filter(|week| {
+            let exists = Path::new(&week.file_name()).exists();
+            if exists && !force {
+                info!("Skipping {}, it already exists.", week.file_name());
+            }
+            force || !exists
+        })
+        .collect::<Vec<_>>();
+
+    let (first, last) = match (weeks.first(), weeks.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => {
+            info!("Nothing to backfill, use -force to regenerate existing posts.");
+            return Ok(());
+        }
+    };
+    let period = AppParams {
+        date: format!(
+            "{}..{}",
+            first.date.split("..").next().unwrap_or_default(),
+            last.date.split("..").last().unwrap_or_default()
+        ),
+        ..app_params.clone()
+    };
+    let (items, _data_quality) = fetch_items(&period).await?;
+
+    for week in weeks {
+        let week_items = items
+            .iter()
+            .filter(|item| week.includes(item_timestamp(item, &week.query_type)))
+            .cloned()
+            .collect::<Vec<_>>();
+        write_items_file(&week, &week_items)?;
+        info!("Wrote {} with {} PRs", week.file_name(), week_items.len());
+        save_last_run(&week, file_config)?;
+    }
+
+    Ok(())
+}
+
+/// Remembers where the published range ended, so the next run continues from there.
+fn save_last_run(
+    app_params: &AppParams,
+    file_config: &mut Option<FileConfig>,
+) -> error::Result<()> {
+    if let Some(file_config) = file_config.as_mut() {
+        if cli::record_last_run(file_config, &app_params.date, &app_params.date_sign) {
+            write_config(app_params, file_config)?;
+        }
+    }
+    Ok(())
+}
+
+/// The post, its `-json` output and the config with the updated `last_run`.
+fn published_paths(app_params: &AppParams) -> Vec<String> {
+    let mut paths = vec![app_params.file_name()];
+    if app_params.json {
+        paths.push(app_params.json_file_name());
+    }
+    if !app_params.config_path.is_empty() {
+        paths.push(app_params.config_path.clone());
+    }
+    paths
+}
+
+async fn publish_twios_file(
+    app_params: &AppParams,
+    mut file_config: Option<FileConfig>,
+) -> error::Result<()> {
+    let (unknown_items, data_quality) = write_twios_file(app_params).await?;
+    save_last_run(app_params, &mut file_config)?;
+    let repository = git2::Repository::discover(".")
+        .map_err(|error| TwiosError::Io(format!("Couldn't find a git repository: {}", error)))?;
+    let branch = publish::branch_name(&app_params.file_name());
+
+    let paths = published_paths(app_params);
+    let query_type = match app_params.query_type {
+        PullRequestQueryType::Merged => "merged",
+        PullRequestQueryType::Created => "created",
+        PullRequestQueryType::Both => "merged or created",
+    };
+    let message = format!(
+        "TWIOS {}\n\nPull requests {} {}{} by {}.",
+        branch.trim_start_matches("twios-"),
+        query_type,
+        app_params.date_sign,
+        app_params.date,
+        app_params.users.join(", "),
+    );
+    let commit = publish::commit(&repository, &branch, &paths, &message)
+        .map_err(|error| TwiosError::Io(format!("Couldn't commit TWIOS: {}", error)))?;
+    info!("Committed {} to {}", commit, branch);
+
+    if app_params.push {
+        publish::push(&repository, &app_params.remote, &branch)
+            .map_err(|error| TwiosError::Network(format!("Couldn't push TWIOS: {}", error)))?;
+        info!("Pushed {} to {}", branch, app_params.remote);
+    }
+
+    if app_params.pull_request_base.len() > 0 {
+        let remote_url = repository
+            .find_remote(&app_params.remote)
+            .ok()
+            .and_then(|remote| remote.url().map(|url| url.to_string()))
+            .unwrap_or_default();
+        let (owner, repo) = publish::github_repository(&remote_url).ok_or_else(|| {
+            TwiosError::Config(format!(
+                "Remote {} is not a GitHub repository",
+                app_params.remote
+            ))
+        })?;
+
+        let mut comment_content: Vec<String> = vec![];
+        write_twios_comment_contents(&mut comment_content, app_params, &unknown_items);
+        if app_params.data_quality_note {
+            write_data_quality_contents(&mut comment_content, &data_quality);
+        }
+
+        let pull_request = initialize_octocrab(app_params)
+            .await?
+            .pulls(owner, repo)
+            .create(
+                format!("TWIOS {}", branch.trim_start_matches("twios-")),
+                branch.clone(),
+                app_params.pull_request_base.clone(),
+            )
+            .body(comment_content.join("\n"))
+            .send()
+            .await?;
+        info!(
+            "Opened {}",
+            pull_request
+                .html_url
+                .map(|url| url.to_string())
+                .unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+async fn serve(app_params: &AppParams, file_config: Option<FileConfig>) -> error::Result<()> {
+    let file_config = require_file_config(file_config)?;
+    if app_params.webhook_secret.is_empty() {
+        return Err(TwiosError::Config(
+            "--webhook-secret or TWIOS_WEBHOOK_SECRET is required to verify webhooks".to_string(),
+        ));
+    }
+
+    let server = tiny_http::Server::http(("0.0.0.0", app_params.port)).map_err(|error| {
+        TwiosError::Network(format!("Couldn't start the webhook server: {}", error))
+    })?;
+    let server = Arc::new(server);
+    info!("Listening for GitHub webhooks on port {}", app_params.port);
+
+    let app_params = Arc::new(app_params.clone());
+    let file_config = Arc::new(tokio::sync::Mutex::new(file_config));
+    loop {
+        // tiny_http blocks while waiting for a request, every webhook is then handled in a task of its own
+        // so a slow regeneration doesn't hold up the next one.
+        let server = server.clone();
+        let received = tokio::task::spawn_blocking(move || {
+            server.recv().map(|mut request| {
+                let webhook = server::WebhookRequest::read(&mut request);
+                (request, webhook)
+            })
+        })
+        .await
+        .map_err(|error| TwiosError::Network(format!("Webhook server stopped: {}", error)))?;
+        let (request, webhook) = received
+            .map_err(|error| TwiosError::Network(format!("Webhook server stopped: {}", error)))?;
+
+        tokio::spawn(handle_webhook(
+            request,
+            webhook,
+            app_params.clone(),
+            file_config.clone(),
+        ));
+    }
+}
+
+/// Applies a TWIOS comment to the configuration and regenerates its post.
+/// The configuration stays locked while it's updated, the regeneration runs alongside other webhooks.
+async fn handle_webhook(
+    request: tiny_http::Request,
+    webhook: io::Result<server::WebhookRequest>,
+    app_params: Arc<AppParams>,
+    file_config: Arc<tokio::sync::Mutex<FileConfig>>,
+) {
+    let webhook = match webhook {
+        Ok(webhook) => webhook,
+        Err(error) => {
+            server::respond(request, 400, &error.to_string());
+            return;
+        }
+    };
+
+    let body = match webhook
+        .verify(&app_params.webhook_secret)
+        .and_then(|_| webhook.comment_body())
+    {
+        Ok(Some(body)) => body,
+        Ok(None) => {
+            server::respond(request, 202, "Ignored");
+            return;
+        }
+        Err(error) => {
+            warn!("Rejected {} webhook: {:?}", webhook.event, error);
+            server::respond(request, error.status_code(), &format!("{:?}", error));
+            return;
+        }
+    };
+
+    let edit_params = {
+        let mut file_config = file_config.lock().await;
+        let mut output = cli::TwiosComment { body }.read();
+        *file_config = cli::merge_with_file_config(&mut output, file_config.clone());
+        match write_config(&app_params, &file_config)
+            .and_then(|_| app_params.with_file_config(&file_config))
+        {
+            Ok(edit_params) => edit_params,
+            Err(error) => {
+                error!("Couldn't apply the comment: {}", error);
+                server::respond(request, 500, "Couldn't apply the comment");
+                return;
+            }
+        }
+    };
+    match write_twios_file(&edit_params).await {
+        Ok(_) => {
+            info!("Regenerated {}", edit_params.file_name());
+            server::respond(request, 200, &edit_params.file_name());
+        }
+        Err(error) => {
+            error!(
+                "Couldn't regenerate {}: {:?}",
+                edit_params.file_name(),
+                error
+            );
+            server::respond(request, 500, "Couldn't regenerate TWIOS");
+        }
+    }
+}
+
+async fn lint_config(app_params: &AppParams) -> error::Result<()> {
+    let contents = fs::read_to_string(&app_params.config_path).map_err(|error| {
+        TwiosError::Io(format!(
+            "Couldn't read {}: {}",
+            app_params.config_path, error
+        ))
+    })?;
+    let value: serde_json::Value = serde_json::from_str(&contents).map_err(|error| {
+        TwiosError::Config(format!(
+            "{} is not valid JSON: {}",
+            app_params.config_path, error
+        ))
+    })?;
+
+    let mut issues = lint::validate_schema(&value);
+    let mut file_config: Option<FileConfig> = serde_json::from_value(value).ok();
+
+    if let Some(file_config) = file_config.as_mut() {
+        issues.append(&mut lint::check_repo_names(file_config));
+        issues.append(&mut lint::check_bot_patterns(file_config));
+        issues.append(&mut lint::check_timezone(file_config));
+        issues.extend(
+            filters::validate_filters(&file_config.filters)
+                .into_iter()
+                .map(|(index, message)| lint::LintIssue {
+                    location: format!("filters.{}", index),
+                    message,
+                }),
+        );
+
+        if app_params.online {
+            let octocrab = initialize_octocrab(app_params).await?;
+            let mut renames: Vec<(String, String)> = vec![];
+
+            for (location, repo) in lint::config_repos(file_config)
+                .into_iter()
+                .filter(|(_, repo)| !lint::is_source_repo(repo))
+            {
+                match lint::resolve_repo(&octocrab, &repo).await {
+                    lint::RepoStatus::Found {
+                        full_name,
+                        archived,
+                    } => {
+                        if full_name != repo {
+                            println!("{}: {} was renamed to {}", location, repo, full_name);
+                            renames.push((repo.clone(), full_name.clone()));
+                        }
+                        if archived {
+                            issues.push(lint::LintIssue {
+                                location,
+                                message: format!("{} is archived", full_name),
+                            });
+                        }
+                    }
+                    lint::RepoStatus::Missing => issues.push(lint::LintIssue {
+                        location,
+                        message: format!("{} doesn't exist or was deleted", repo),
+                    }),
+                    lint::RepoStatus::Failed(error) => issues.push(lint::LintIssue {
+                        location,
+                        message: format!("Couldn't resolve {}: {}", repo, error),
+                    }),
+                }
+            }
+
+            if app_params.fix && !renames.is_empty() {
+                lint::rename_repos(file_config, &renames);
+                write_config(app_params, file_config)?;
+                info!(
+                    "Renamed {} repos in {}",
+                    renames.len(),
+                    app_params.config_path
+                );
+            } else if !renames.is_empty() {
+                issues.extend(renames.into_iter().map(|(from, to)| lint::LintIssue {
+                    location: "".to_string(),
+                    message: format!("{} should be renamed to {}, run with -fix", from, to),
+                }));
+            }
+        }
+    }
+
+    for issue in issues.iter() {
+        println!("{}: {}", issue.location, issue.message);
+    }
+    if !issues.is_empty() {
+        std::process::exit(1);
+    }
+    println!("{} looks good.", app_params.config_path);
+
+    Ok(())
 }
 
 #[tokio::main(flavor = "current_thread")]
-async fn main() -> octocrab::Result<()> {
-    println!("Using this-week-in-open-source v{}", VERSION);
-    println!("");
+async fn main() {
+    logger::init();
+    if let Err(error) = run().await {
+        error!("{}", error);
+        std::process::exit(error.exit_code());
+    }
+}
 
-    let (app_params, file_config) = args();
+async fn run() -> error::Result<()> {
+    let (app_params, file_config) = args()?;
+    info!("Using this-week-in-open-source v{}", VERSION);
 
     match app_params.context {
         cli::CliContext::TWIOS => {
-            let (labels, unknown_items, markdown_definitions) = fetch_data(&app_params).await?;
-            let mut file = File::create(app_params.file_name()).unwrap();
-            let mut file_content: Vec<String> = vec![];
-            write_twios_file_contents(&mut file_content, &labels, &unknown_items);
-
-            file.write_all(app_params.header.join("\n").as_bytes())
-                .unwrap();
-            file.write_all(file_content.join("\n").as_bytes()).unwrap();
-            file.write(BREAK_LINE.as_bytes()).unwrap();
-            file.write_all(markdown_definitions.join("\n").as_bytes())
-                .unwrap();
-            println!("");
-            println!("Done! :)");
+            let mut file_config = file_config;
+            if app_params.backfill {
+                backfill(&app_params, true, &mut file_config).await?;
+            } else {
+                write_twios_file(&app_params).await?;
+                save_last_run(&app_params, &mut file_config)?;
+            }
+            info!("Done! :)");
         }
         cli::CliContext::COMMENT => {
-            let (_labels, unknown_items, _markdown_definitions) = fetch_data(&app_params).await?;
+            let (_labels, unknown_items, _markdown_definitions, data_quality) =
+                fetch_data(&app_params).await?;
             let mut comment_content: Vec<String> = vec![];
             write_twios_comment_contents(&mut comment_content, &app_params, &unknown_items);
+            if app_params.data_quality_note {
+                write_data_quality_contents(&mut comment_content, &data_quality);
+            }
             let twios_comment = cli::TwiosComment {
                 body: app_params.comment_body.clone(),
             };
 
             let mut output = twios_comment.read();
 
-            cli::write_config_to_file(
-                app_params.config_path.clone(),
-                &cli::merge_with_file_config(&mut output, file_config.unwrap()),
-            )
-            .unwrap();
+            write_config(
+                &app_params,
+                &cli::merge_with_file_config(&mut output, require_file_config(file_config)?),
+            )?;
             io::stdout()
                 .write_all(comment_content.join("\n").as_bytes())
-                .unwrap();
+                .map_err(|error| TwiosError::Io(format!("Couldn't write to stdout: {}", error)))?;
         }
         cli::CliContext::UTILITY => {
-            if app_params.dedupe {
-                let mut config = file_config
-                    .expect("Configuration file doesn't exist")
-                    .clone();
-                cli::dedupe_file_config(&mut config);
-                cli::write_config_to_file(app_params.config_path.clone(), &config)
-                    .expect("Couldn't write to file");
+            if app_params.dedupe || app_params.check {
+                let original = require_file_config(file_config)?;
+                let mut config = original.clone();
+                let conflicts = cli::normalize_file_config(&mut config);
+
+                for conflict in conflicts.iter() {
+                    println!(
+                        "{} is claimed by: {}",
+                        conflict.repo,
+                        conflict.claimed_by.join(", ")
+                    );
+                }
+
+                let is_normalized =
+                    serde_json::to_value(&original).ok() == serde_json::to_value(&config).ok();
+                if app_params.check {
+                    if !is_normalized {
+                        println!("{} is not normalized.", app_params.config_path);
+                        println!("Run `utility -dedupe` to normalize it.");
+                        std::process::exit(1);
+                    }
+                } else {
+                    write_config(&app_params, &config)?;
+                }
             }
         }
+        cli::CliContext::SERVE => serve(&app_params, file_config).await?,
+        cli::CliContext::PUBLISH => publish_twios_file(&app_params, file_config).await?,
+        cli::CliContext::LINT => lint_config(&app_params).await?,
+        cli::CliContext::BACKFILL => {
+            let mut file_config = file_config;
+            backfill(&app_params, app_params.force, &mut file_config).await?;
+        }
     }
 
     Ok(())
@@ -388,7 +1415,7 @@ async fn main() -> octocrab::Result<()> {
 mod tests {
     use super::*;
 
-    fn items_helper() -> Vec<Item> {
+    pub(crate) fn items_helper() -> Vec<Item> {
         vec![
             Item {
                 issue_number: "63".to_string(),
@@ -400,8 +1427,20 @@ mod tests {
                 repository_url: "https://github.com/atom/keyboard-layout".to_string(),
                 user_login: "mansona".to_string(),
                 user_url: "https://github.com/mansona".to_string(),
+                user_name: None,
                 state: "closed".to_string(),
                 merge_status: ItemMergeStatus::Unknown,
+                created_at: "2021-12-01T10:00:00Z".parse().unwrap(),
+                closed_at: None,
+                merged_at: None,
+                labels: vec![],
+                comments: 0,
+                body: None,
+                draft: false,
+                additions: None,
+                deletions: None,
+                changed_files: None,
+                bot: false,
             },
             Item {
                 issue_number: "798".to_string(),
@@ -413,8 +1452,20 @@ mod tests {
                 repository_url: "https://github.com/ember-engines/ember-engines".to_string(),
                 user_login: "BobrImperator".to_string(),
                 user_url: "https://github.com/BobrImperator".to_string(),
+                user_name: None,
                 state: "open".to_string(),
                 merge_status: ItemMergeStatus::Unknown,
+                created_at: "2021-12-01T10:00:00Z".parse().unwrap(),
+                closed_at: None,
+                merged_at: None,
+                labels: vec![],
+                comments: 0,
+                body: None,
+                draft: false,
+                additions: None,
+                deletions: None,
+                changed_files: None,
+                bot: false,
             },
         ]
     }
@@ -434,7 +1485,7 @@ mod tests {
     fn it_formats_item() {
         assert_eq!(
             "- [atom/keyboard-layout] [#63](https://github.com/atom/keyboard-layout/pull/63) Update nan ([@mansona])",
-            format_item("mansona".to_string(), &items_helper()[0])
+            format_item("@mansona".to_string(), &items_helper()[0])
         );
     }
 
@@ -458,6 +1509,31 @@ mod tests {
         assert_eq!(expected, extract_definitions(&items_helper()));
     }
 
+    #[test]
+    fn it_references_people_by_name() {
+        let items = items_helper()
+            .into_iter()
+            .map(|item| Item {
+                user_name: Some("Jane Doe".to_string()),
+                user_url: "https://janedoe.dev".to_string(),
+                ..item
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            "- [atom/keyboard-layout] [#63](https://github.com/atom/keyboard-layout/pull/63) Update nan ([Jane Doe])",
+            format_items(&items)[0]
+        );
+        assert_eq!(
+            vec!["[Jane Doe]: https://janedoe.dev"],
+            extract_definitions(&items)[..1]
+        );
+        assert_eq!(
+            "[atom/keyboard-layout]: https://github.com/atom/keyboard-layout",
+            extract_definitions(&items)[1]
+        );
+    }
+
     #[test]
     fn it_matches_items_with_labels() {
         let items = items_helper();
@@ -496,8 +1572,20 @@ mod tests {
                 repository_url: "https://github.com/atom/keyboard-layout".to_string(),
                 user_login: "mansona".to_string(),
                 user_url: "https://github.com/mansona".to_string(),
+                user_name: None,
                 state: "closed".to_string(),
                 merge_status: ItemMergeStatus::NotMerged,
+                created_at: "2021-12-01T10:00:00Z".parse().unwrap(),
+                closed_at: Some("2021-12-02T10:00:00Z".parse().unwrap()),
+                merged_at: None,
+                labels: vec![],
+                comments: 0,
+                body: None,
+                draft: false,
+                additions: None,
+                deletions: None,
+                changed_files: None,
+                bot: false,
             },
             Item {
                 issue_number: "798".to_string(),
@@ -509,10 +1597,432 @@ mod tests {
                 repository_url: "https://github.com/ember-engines/ember-engines".to_string(),
                 user_login: "BobrImperator".to_string(),
                 user_url: "https://github.com/BobrImperator".to_string(),
+                user_name: None,
                 state: "open".to_string(),
                 merge_status: ItemMergeStatus::Unknown,
+                created_at: "2021-12-01T10:00:00Z".parse().unwrap(),
+                closed_at: None,
+                merged_at: None,
+                labels: vec![],
+                comments: 0,
+                body: None,
+                draft: false,
+                additions: None,
+                deletions: None,
+                changed_files: None,
+                bot: false,
             },
         ];
         assert_eq!(vec![items[1].clone()], filter_items_by_merge_status(items))
     }
+
+    #[test]
+    fn it_reads_repository_from_url() {
+        assert_eq!(
+            Some((
+                "mainmatter".to_string(),
+                "twios".to_string(),
+                "https://github.com/mainmatter/twios".to_string()
+            )),
+            repository("https://github.com/mainmatter/twios/pull/42")
+        );
+        assert_eq!(
+            Some((
+                "mainmatter".to_string(),
+                "twios".to_string(),
+                "https://github.example.com:8443/mainmatter/twios".to_string()
+            )),
+            repository("https://github.example.com:8443/mainmatter/twios/pull/42")
+        );
+        assert_eq!(None, repository("https://github.example.com/mainmatter"));
+    }
+
+    #[tokio::test]
+    async fn it_searches_custom_api_url() {
+        let (base_url, requested_url) =
+            sources::mock_server(200, include_str!("../fixtures/github/search_issues.json"));
+        // GitHub Enterprise Server serves the REST API below a path
+        let octocrab = build_octocrab(&format!("{}api/v3", base_url), None).unwrap();
+
+        let page = get_prs(
+            &octocrab,
+            &"BobrImperator".to_string(),
+            &"2023-09-11..2023-09-17".to_string(),
+            "merged",
+        )
+        .await
+        .unwrap();
+
+        assert!(requested_url
+            .recv()
+            .unwrap()
+            .starts_with("/api/v3/search/issues?q="));
+        assert_eq!(
+            "https://github.example.com/mainmatter/twios/pull/42",
+            page.items[0].html_url.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn it_reports_exhausted_rate_limit() {
+        let (base_url, _) = sources::mock_server(
+            403,
+            r#"{"message":"API rate limit exceeded for 127.0.0.1.","documentation_url":"https://docs.github.com/rest/overview/resources-in-the-rest-api#rate-limiting"}"#,
+        );
+        let octocrab = build_octocrab(&base_url, None).unwrap();
+
+        let error: TwiosError = get_prs(
+            &octocrab,
+            &"BobrImperator".to_string(),
+            &"2023-09-11..2023-09-17".to_string(),
+            "merged",
+        )
+        .await
+        .unwrap_err()
+        .into();
+
+        assert_eq!(4, error.exit_code());
+        assert!(error.to_string().contains("API rate limit exceeded"));
+    }
+
+    #[tokio::test]
+    async fn it_keeps_searching_after_a_failed_user() {
+        let (base_url, requested_url) = sources::mock_server_responses(vec![
+            (
+                422,
+                r#"{"message":"Validation Failed","documentation_url":"https://docs.github.com/v3/search/"}"#,
+            ),
+            (200, include_str!("../fixtures/github/search_issues.json")),
+        ]);
+        let octocrab = build_octocrab(&base_url, None).unwrap();
+        let app_params = AppParams {
+            users: vec!["BobrImperator".to_string(), "mansona".to_string()],
+            date: "2023-09-11..2023-09-17".to_string(),
+            query_type: PullRequestQueryType::Merged,
+            ..cli::tests::app_params_helper()
+        };
+
+        let (items, failed_users) = get_user_items(&octocrab, &app_params).await.unwrap();
+
+        assert_eq!(1, failed_users.len());
+        assert_eq!("BobrImperator", failed_users[0].0);
+        assert_eq!(1, items.len());
+        assert_eq!(
+            "https://github.example.com/mainmatter/twios/pull/42",
+            items[0].issue_url
+        );
+        assert!(requested_url
+            .recv()
+            .unwrap()
+            .contains("author%3ABobrImperator"));
+        assert!(requested_url.recv().unwrap().contains("author%3Amansona"));
+    }
+
+    #[tokio::test]
+    async fn it_splits_truncated_searches() {
+        let (base_url, requested_url) = sources::mock_server_responses(vec![
+            (
+                200,
+                include_str!("../fixtures/github/search_issues_truncated.json"),
+            ),
+            (200, include_str!("../fixtures/github/search_issues.json")),
+            (
+                200,
+                include_str!("../fixtures/github/search_issues_other.json"),
+            ),
+        ]);
+        let octocrab = build_octocrab(&base_url, None).unwrap();
+        let app_params = AppParams {
+            date: "2023-09-11..2023-09-17".to_string(),
+            query_type: PullRequestQueryType::Merged,
+            ..cli::tests::app_params_helper()
+        };
+        let mut items = vec![];
+
+        get_items_of_user(
+            &octocrab,
+            &app_params,
+            &"BobrImperator".to_string(),
+            &["merged"],
+            &mut items,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                "https://github.example.com/mainmatter/twios/pull/42",
+                "https://github.example.com/mainmatter/twios/pull/43",
+            ],
+            items
+                .iter()
+                .map(|item| item.issue_url.as_str())
+                .collect::<Vec<_>>()
+        );
+        let whole_period = requested_url.recv().unwrap();
+        let first_half = requested_url.recv().unwrap();
+        let second_half = requested_url.recv().unwrap();
+        assert!(whole_period.contains("merged%3A2023-09-11..2023-09-17"));
+        assert!(first_half.contains(
+            "merged%3A2023-09-11T00%3A00%3A00%2B00%3A00..2023-09-14T11%3A59%3A59%2B00%3A00"
+        ));
+        assert!(second_half.contains(
+            "merged%3A2023-09-14T12%3A00%3A00%2B00%3A00..2023-09-17T23%3A59%3A59%2B00%3A00"
+        ));
+    }
+
+    #[test]
+    fn it_publishes_json_output() {
+        let app_params = AppParams {
+            date: "2023-09-11..2023-09-17".to_string(),
+            output_path: "twios".to_string(),
+            config_path: "sample_config.json".to_string(),
+            ..cli::tests::app_params_helper()
+        };
+        assert_eq!(
+            vec![app_params.file_name(), "sample_config.json".to_string()],
+            published_paths(&app_params)
+        );
+
+        let app_params = AppParams {
+            json: true,
+            ..app_params
+        };
+        assert_eq!(
+            vec![
+                app_params.file_name(),
+                app_params.json_file_name(),
+                "sample_config.json".to_string()
+            ],
+            published_paths(&app_params)
+        );
+        assert!(app_params.json_file_name().ends_with("2023-09-17.json"));
+    }
+
+    #[tokio::test]
+    async fn it_keeps_merged_status_without_details() {
+        let (base_url, _) = sources::mock_server(500, r#"{"message":"Server Error"}"#);
+        let octocrab = build_octocrab(&base_url, None).unwrap();
+        let mut items = items_helper();
+        items.truncate(1);
+        items[0].merge_status = ItemMergeStatus::Merged;
+
+        set_pull_request_details(&octocrab, &mut items).await;
+
+        assert_eq!(ItemMergeStatus::Merged, items[0].merge_status);
+    }
+
+    #[test]
+    fn it_detects_truncated_search_results() {
+        assert!(!is_truncated(Some(1000)));
+        assert!(is_truncated(Some(1001)));
+        assert!(!is_truncated(None));
+    }
+
+    #[test]
+    fn it_detects_automated_prs() {
+        let bots = BotConfig {
+            title_patterns: vec![r"^chore\(deps\)".to_string()],
+            apps: true,
+            section: false,
+            patterns: vec![],
+        }
+        .compile()
+        .unwrap();
+
+        assert!(is_automated(
+            &BotConfig::default(),
+            "dependabot[bot]",
+            "Bot",
+            "Bump serde"
+        ));
+        assert!(!is_automated(
+            &BotConfig::default(),
+            "renovate",
+            "Bot",
+            "Update serde"
+        ));
+        assert!(is_automated(&bots, "renovate", "Bot", "Update serde"));
+        assert!(is_automated(
+            &bots,
+            "BobrImperator",
+            "User",
+            "Chore(deps): bump serde"
+        ));
+        assert!(!is_automated(
+            &bots,
+            "BobrImperator",
+            "User",
+            "Ember 4 compatibility"
+        ));
+    }
+
+    #[test]
+    fn it_writes_bots_section() {
+        let mut items = items_helper();
+        items[0].bot = true;
+        let mut content = vec![];
+        write_bots_contents(&mut content, &vec![items[0].clone()]);
+        assert_eq!(
+            vec![
+                "",
+                "<details><summary>Automated PRs (1)</summary>",
+                "",
+                "- [atom/keyboard-layout] [#63](https://github.com/atom/keyboard-layout/pull/63) Update nan ([@mansona])",
+                "",
+                "</details>",
+            ],
+            content
+        );
+    }
+
+    #[test]
+    fn it_writes_own_organizations_section() {
+        let mut content = vec![];
+        write_own_organizations_contents(&mut content, &vec![], &PullRequestQueryType::Created);
+        assert_eq!(0, content.len());
+
+        write_own_organizations_contents(
+            &mut content,
+            &vec![items_helper()[0].clone()],
+            &PullRequestQueryType::Created,
+        );
+        assert_eq!(
+            vec![
+                "",
+                "## Own organizations",
+                "",
+                "- [atom/keyboard-layout] [#63](https://github.com/atom/keyboard-layout/pull/63) Update nan ([@mansona])",
+            ],
+            content
+        );
+    }
+
+    #[test]
+    fn it_writes_data_quality_section() {
+        let mut content = vec![];
+        write_data_quality_contents(&mut content, &DataQuality::default());
+        assert_eq!(0, content.len());
+
+        let mut items = items_helper();
+        items[0].merge_status = ItemMergeStatus::Unknown;
+        let data_quality = DataQuality {
+            failed_users: vec![(
+                "BobrImperator".to_string(),
+                "Request failed: Validation Failed".to_string(),
+            )],
+            failed_sources: vec![],
+            unknown_items: vec![items[0].clone()],
+        };
+        write_data_quality_contents(&mut content, &data_quality);
+        assert_eq!(
+            vec![
+                "",
+                "### Data quality",
+                "",
+                "- Couldn't search the PRs of BobrImperator: Request failed: Validation Failed",
+                "- Merge status of https://github.com/atom/keyboard-layout/pull/63 is unknown",
+            ],
+            content
+        );
+    }
+
+    #[test]
+    fn it_sorts_items() {
+        let mut items = items_helper();
+        items[0].additions = Some(10);
+        items[0].deletions = Some(0);
+        let mut small = items[1].clone();
+        small.issue_number = "12".to_string();
+        small.additions = Some(0);
+        small.deletions = Some(1);
+        items.push(small);
+
+        sort_items(&mut items, &vec![]);
+        assert_eq!(
+            vec!["63", "798", "12"],
+            items
+                .iter()
+                .map(|item| item.issue_number.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        sort_items(
+            &mut items,
+            &vec![
+                SortRule {
+                    by: SortKey::Author,
+                    order: SortOrder::Ascending,
+                },
+                SortRule {
+                    by: SortKey::Number,
+                    order: SortOrder::Descending,
+                },
+            ],
+        );
+        assert_eq!(
+            vec!["798", "12", "63"],
+            items
+                .iter()
+                .map(|item| item.issue_number.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        sort_items(
+            &mut items,
+            &vec![SortRule {
+                by: SortKey::Size,
+                order: SortOrder::Descending,
+            }],
+        );
+        assert_eq!("63", items[0].issue_number);
+        assert_eq!("798", items[2].issue_number);
+        sort_items(
+            &mut items,
+            &vec![SortRule {
+                by: SortKey::Size,
+                order: SortOrder::Ascending,
+            }],
+        );
+        assert_eq!("12", items[0].issue_number);
+        // Its size was never fetched
+        assert_eq!("798", items[2].issue_number);
+
+        items[0].merged_at = Some("2021-12-02T10:00:00Z".parse().unwrap());
+        items[1].merged_at = None;
+        items[2].merged_at = Some("2021-12-01T10:00:00Z".parse().unwrap());
+        for order in [SortOrder::Ascending, SortOrder::Descending] {
+            sort_items(
+                &mut items,
+                &vec![SortRule {
+                    by: SortKey::Merged,
+                    order,
+                }],
+            );
+            assert_eq!(None, items[2].merged_at);
+        }
+        assert_eq!("12", items[0].issue_number);
+    }
+
+    #[test]
+    fn it_formats_activity_items() {
+        let mut items = items_helper();
+        items[0].merge_status = ItemMergeStatus::Merged;
+        let mut closed = items[1].clone();
+        closed.issue_number = "799".to_string();
+        closed.state = "closed".to_string();
+        closed.merge_status = ItemMergeStatus::NotMerged;
+        items.push(closed);
+
+        let expected = vec![
+            "### Merged this week",
+            "",
+            "- [atom/keyboard-layout] [#63](https://github.com/atom/keyboard-layout/pull/63) Update nan ([@mansona])",
+            "",
+            "### Opened this week (still open)",
+            "",
+            "- [ember-engines/ember-engines] [#798](https://github.com/ember-engines/ember-engines/pull/798) Ember 4 compatibility ([@BobrImperator])",
+        ];
+        assert_eq!(expected, format_activity_items(&items));
+    }
 }
@@ -0,0 +1,278 @@
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, warn};
+use octocrab::Octocrab;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const DEFAULT_CACHE_PATH: &str = ".twios-members.json";
+const CACHE_HOURS: i64 = 24;
+const PER_PAGE: usize = 100;
+
+/// An entry of `users`: a login, `org:<org>` or `team:<org>/<team-slug>`.
+#[derive(PartialEq, Debug, Clone)]
+pub enum UserEntry {
+    User(String),
+    Organization(String),
+    Team { organization: String, slug: String },
+}
+
+impl UserEntry {
+    pub fn parse(entry: &str) -> Self {
+        let entry = entry.trim();
+        if let Some(organization) = entry.strip_prefix("org:") {
+            return UserEntry::Organization(organization.to_string());
+        }
+        if let Some((organization, slug)) = entry
+            .strip_prefix("team:")
+            .and_then(|team| team.split_once('/'))
+        {
+            return UserEntry::Team {
+                organization: organization.to_string(),
+                slug: slug.to_string(),
+            };
+        }
+        UserEntry::User(entry.to_string())
+    }
+
+    fn members_route(&self, page: usize) -> Option<String> {
+        match self {
+            UserEntry::User(_) => None,
+            UserEntry::Organization(organization) => Some(format!(
+                "orgs/{}/members?per_page={}&page={}",
+                organization, PER_PAGE, page
+            )),
+            UserEntry::Team { organization, slug } => Some(format!(
+                "orgs/{}/teams/{}/members?per_page={}&page={}",
+                organization, slug, PER_PAGE, page
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Member {
+    login: String,
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedMembers {
+    pub fetched_at: DateTime<Utc>,
+    pub logins: Vec<String>,
+}
+
+impl CachedMembers {
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        now - self.fetched_at < Duration::hours(CACHE_HOURS)
+    }
+}
+
+/// Members of every `org:`/`team:` entry by the entry.
+pub type MembersCache = BTreeMap<String, CachedMembers>;
+
+pub fn read_cache<P: AsRef<Path>>(path: P) -> MembersCache {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+pub fn write_cache<P: AsRef<Path>>(path: P, cache: &MembersCache) -> std::io::Result<()> {
+    fs::write(path, serde_json::to_string_pretty(cache)?)
+}
+
+async fn fetch_members(octocrab: &Octocrab, entry: &UserEntry) -> octocrab::Result<Vec<String>> {
+    let mut logins = vec![];
+
+    for page in 1.. {
+        let route = match entry.members_route(page) {
+            Some(route) => route,
+            None => break,
+        };
+        debug!("Fetching {}", route);
+        let members: Vec<Member> = octocrab.get(route, None::<&()>).await?;
+        let is_last_page = members.len() < PER_PAGE;
+        logins.extend(members.into_iter().map(|member| member.login));
+        if is_last_page {
+            break;
+        }
+    }
+
+    Ok(logins)
+}
+
+/// Explicit logins followed by the members of organizations and teams, without duplicates
+/// and without `ignore_users`. Logins are compared ignoring case.
+pub fn merge_users(logins: Vec<String>, ignore_users: &[String]) -> Vec<String> {
+    let mut users: Vec<String> = vec![];
+
+    for login in logins {
+        let is_ignored = ignore_users
+            .iter()
+            .any(|ignored| ignored.eq_ignore_ascii_case(&login));
+        let is_duplicate = users.iter().any(|user| user.eq_ignore_ascii_case(&login));
+        if !is_ignored && !is_duplicate {
+            users.push(login);
+        }
+    }
+
+    users
+}
+
+/// Resolves `org:` and `team:` entries to member logins. Members are cached in `cache_path`
+/// for a day, an outdated cache is still used when GitHub can't be reached.
+pub async fn resolve_users(
+    octocrab: &Octocrab,
+    users: &[String],
+    ignore_users: &[String],
+    cache_path: &str,
+    refresh: bool,
+) -> Vec<String> {
+    let now = Utc::now();
+    let mut cache = read_cache(cache_path);
+    let mut is_cache_updated = false;
+    let mut explicit = vec![];
+    let mut members = vec![];
+
+    for user in users {
+        let entry = UserEntry::parse(user);
+        if let UserEntry::User(login) = entry {
+            explicit.push(login);
+            continue;
+        }
+
+        let cached = cache
+            .get(user)
+            .filter(|cached| !refresh && cached.is_fresh(now));
+        if let Some(cached) = cached {
+            debug!("Using members of {} cached at {}", user, cached.fetched_at);
+            members.extend(cached.logins.clone());
+            continue;
+        }
+
+        match fetch_members(octocrab, &entry).await {
+            Ok(logins) => {
+                members.extend(logins.clone());
+                cache.insert(
+                    user.clone(),
+                    CachedMembers {
+                        fetched_at: now,
+                        logins,
+                    },
+                );
+                is_cache_updated = true;
+            }
+            Err(error) => {
+                warn!("Couldn't resolve members of {}: {:?}", user, error);
+                if let Some(cached) = cache.get(user) {
+                    warn!("Using members cached at {}", cached.fetched_at);
+                    members.extend(cached.logins.clone());
+                }
+            }
+        }
+    }
+
+    if is_cache_updated {
+        if let Err(error) = write_cache(cache_path, &cache) {
+            warn!("Couldn't write {}: {}", cache_path, error);
+        }
+    }
+
+    explicit.append(&mut members);
+    merge_users(explicit, ignore_users)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn it_parses_user_entries() {
+        assert_eq!(
+            UserEntry::User("BobrImperator".to_string()),
+            UserEntry::parse("BobrImperator")
+        );
+        assert_eq!(
+            UserEntry::Organization("mainmatter".to_string()),
+            UserEntry::parse("org:mainmatter")
+        );
+        assert_eq!(
+            UserEntry::Team {
+                organization: "mainmatter".to_string(),
+                slug: "engineering".to_string(),
+            },
+            UserEntry::parse("team:mainmatter/engineering")
+        );
+    }
+
+    #[test]
+    fn it_builds_members_routes() {
+        assert_eq!(
+            Some("orgs/mainmatter/teams/engineering/members?per_page=100&page=2".to_string()),
+            UserEntry::parse("team:mainmatter/engineering").members_route(2)
+        );
+        assert_eq!(None, UserEntry::parse("mansona").members_route(1));
+    }
+
+    #[tokio::test]
+    async fn it_fetches_members_below_api_path() {
+        let (base_url, requested_url) =
+            crate::sources::mock_server(200, r#"[{"login":"BobrImperator"},{"login":"mansona"}]"#);
+        let octocrab = crate::build_octocrab(&format!("{}api/v3", base_url), None).unwrap();
+
+        assert_eq!(
+            vec!["BobrImperator".to_string(), "mansona".to_string()],
+            fetch_members(&octocrab, &UserEntry::parse("org:mainmatter"))
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            "/api/v3/orgs/mainmatter/members?per_page=100&page=1",
+            requested_url.recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn it_merges_users() {
+        let logins = vec![
+            "BobrImperator".to_string(),
+            "mansona".to_string(),
+            "bobrimperator".to_string(),
+            "dependabot".to_string(),
+        ];
+
+        assert_eq!(
+            vec!["BobrImperator".to_string(), "mansona".to_string()],
+            merge_users(logins, &["Dependabot".to_string()])
+        );
+    }
+
+    #[test]
+    fn it_caches_members() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("members.json");
+        let fetched_at: DateTime<Utc> = "2023-09-18T10:00:00Z".parse().unwrap();
+
+        let mut cache = MembersCache::new();
+        cache.insert(
+            "org:mainmatter".to_string(),
+            CachedMembers {
+                fetched_at,
+                logins: vec!["BobrImperator".to_string()],
+            },
+        );
+        write_cache(&path, &cache).unwrap();
+
+        let cached = read_cache(&path);
+        assert_eq!(cache, cached);
+        assert!(cached["org:mainmatter"].is_fresh(fetched_at + Duration::hours(23)));
+        assert!(!cached["org:mainmatter"].is_fresh(fetched_at + Duration::hours(24)));
+        assert_eq!(
+            MembersCache::new(),
+            read_cache(dir.path().join("missing.json"))
+        );
+    }
+}
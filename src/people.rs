@@ -0,0 +1,163 @@
+use crate::Item;
+use serde::{Deserialize, Serialize};
+
+/// One person contributing through handles on several platforms.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Person {
+    /// Credited in the post instead of the handle, e.g. `Jane Doe`.
+    pub name: String,
+    /// Profile the name links to, the profile on the platform of the PR is used otherwise.
+    #[serde(default)]
+    pub url: String,
+    /// GitHub logins as they are, handles on other sources prefixed with the source name
+    /// like repository names, e.g. `gitlab:jane` or `codeberg:jdoe`.
+    #[serde(default)]
+    pub handles: Vec<String>,
+}
+
+impl Person {
+    /// Position of the item author's handle in `handles`, if the person authored it.
+    fn handle_index(&self, item: &Item) -> Option<usize> {
+        let handle = match item.full_repository_name.split_once(':') {
+            Some((source, _)) => format!("{}:{}", source, item.user_login),
+            None => item.user_login.clone(),
+        };
+
+        self.handles
+            .iter()
+            .position(|person_handle| person_handle.eq_ignore_ascii_case(&handle))
+    }
+
+    /// `url`, or the profile of the first handle that authored one of `items`.
+    fn profile_url(&self, items: &[Item]) -> Option<String> {
+        if !self.url.is_empty() {
+            return Some(self.url.clone());
+        }
+        items
+            .iter()
+            .filter_map(|item| Some((self.handle_index(item)?, item)))
+            .min_by_key(|(index, _)| *index)
+            .map(|(_, item)| item.user_url.clone())
+    }
+}
+
+/// Credits every item authored by one of `people` to the person's name and profile.
+/// All items of a person link to the same profile, the name is defined once in the post.
+pub fn credit_people(items: &[Item], people: &[Person]) -> Vec<Item> {
+    let profile_urls = people
+        .iter()
+        .map(|person| person.profile_url(items))
+        .collect::<Vec<_>>();
+
+    items
+        .iter()
+        .map(|item| {
+            let person = people
+                .iter()
+                .position(|person| person.handle_index(item).is_some());
+            match person {
+                Some(index) => Item {
+                    user_name: Some(people[index].name.clone()),
+                    user_url: profile_urls[index]
+                        .clone()
+                        .unwrap_or_else(|| item.user_url.clone()),
+                    ..item.clone()
+                },
+                None => item.clone(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item_helper(full_repository_name: &str, user_login: &str, user_url: &str) -> Item {
+        Item {
+            full_repository_name: full_repository_name.to_string(),
+            user_login: user_login.to_string(),
+            user_url: user_url.to_string(),
+            ..crate::tests::items_helper()[0].clone()
+        }
+    }
+
+    fn people_helper() -> Vec<Person> {
+        vec![Person {
+            name: "Jane Doe".to_string(),
+            url: "https://janedoe.dev".to_string(),
+            handles: vec![
+                "janedoe".to_string(),
+                "gitlab:jane".to_string(),
+                "codeberg:jdoe".to_string(),
+            ],
+        }]
+    }
+
+    #[test]
+    fn it_credits_people_across_sources() {
+        let items = vec![
+            item_helper("GNOME/gtk", "JaneDoe", "https://github.com/JaneDoe"),
+            item_helper("gitlab:GNOME/gtk", "jane", "https://gitlab.com/jane"),
+            item_helper(
+                "codeberg:forgejo/forgejo",
+                "jdoe",
+                "https://codeberg.org/jdoe",
+            ),
+        ];
+
+        let credited = credit_people(&items, &people_helper());
+        assert!(credited
+            .iter()
+            .all(|item| item.user_name.as_deref() == Some("Jane Doe")
+                && item.user_url == "https://janedoe.dev"));
+        assert_eq!("Jane Doe", credited[0].author());
+        assert_eq!("JaneDoe", credited[0].user_login);
+    }
+
+    #[test]
+    fn it_keeps_unknown_handles() {
+        let items = vec![
+            // Same handle on another platform doesn't have to be the same person
+            item_helper("gitlab:GNOME/gtk", "janedoe", "https://gitlab.com/janedoe"),
+            item_helper("GNOME/gtk", "mansona", "https://github.com/mansona"),
+        ];
+
+        assert_eq!(items, credit_people(&items, &people_helper()));
+    }
+
+    #[test]
+    fn it_falls_back_to_platform_profile() {
+        let mut people = people_helper();
+        people[0].url = String::new();
+        let items = vec![item_helper(
+            "gitlab:GNOME/gtk",
+            "jane",
+            "https://gitlab.com/jane",
+        )];
+
+        let credited = credit_people(&items, &people);
+        assert_eq!(Some("Jane Doe".to_string()), credited[0].user_name);
+        assert_eq!("https://gitlab.com/jane", credited[0].user_url);
+    }
+
+    #[test]
+    fn it_links_every_platform_to_the_first_handle() {
+        let mut people = people_helper();
+        people[0].url = String::new();
+        let items = vec![
+            item_helper(
+                "codeberg:forgejo/forgejo",
+                "jdoe",
+                "https://codeberg.org/jdoe",
+            ),
+            item_helper("gitlab:GNOME/gtk", "jane", "https://gitlab.com/jane"),
+        ];
+
+        let credited = credit_people(&items, &people);
+        assert!(credited
+            .iter()
+            .all(|item| item.user_url == "https://gitlab.com/jane"));
+    }
+}
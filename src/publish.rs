@@ -0,0 +1,256 @@
+use git2::{
+    BranchType, Cred, CredentialType, Oid, PushOptions, RemoteCallbacks, Repository, Signature,
+};
+use std::env;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_AUTHOR_NAME: &str = "TWIOS";
+const DEFAULT_AUTHOR_EMAIL: &str = "twios@mainmatter.com";
+
+/// `twios/2023-09-18.md` -> `twios-2023-09-18`
+pub fn branch_name(file_name: &str) -> String {
+    let stem = Path::new(file_name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    format!("twios-{}", stem)
+}
+
+/// Extracts `(owner, repository)` from a GitHub remote url,
+/// e.g. `git@github.com:mainmatter/twios.git` or `https://github.com/mainmatter/twios`.
+pub fn github_repository(remote_url: &str) -> Option<(String, String)> {
+    let path = match remote_url.split_once("://") {
+        Some((_scheme, rest)) => rest.split_once('/')?.1,
+        None => remote_url.split_once(':')?.1,
+    };
+    let mut parts = path
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .split('/');
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(owner), Some(repo), None) if !owner.is_empty() && !repo.is_empty() => {
+            Some((owner.to_string(), repo.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Commits `paths` onto `branch`, creating it from `HEAD` unless it's already checked out.
+/// The working tree is left untouched, `HEAD` points to `branch` afterwards.
+pub fn commit(
+    repository: &Repository,
+    branch: &str,
+    paths: &[String],
+    message: &str,
+) -> Result<Oid, git2::Error> {
+    let head = repository.head()?;
+    let parent = head.peel_to_commit()?;
+
+    if head.shorthand() != Some(branch) {
+        repository.branch(branch, &parent, false)?;
+        repository.set_head(&format!("refs/heads/{}", branch))?;
+    }
+
+    let workdir = repository
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("Can't commit to a bare repository"))?;
+    let mut index = repository.index()?;
+    for path in paths {
+        index.add_path(&relative_path(workdir, Path::new(path))?)?;
+    }
+    index.write()?;
+
+    let tree = repository.find_tree(index.write_tree()?)?;
+    let signature = repository
+        .signature()
+        .or_else(|_| Signature::now(DEFAULT_AUTHOR_NAME, DEFAULT_AUTHOR_EMAIL))?;
+
+    repository.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &[&parent],
+    )
+}
+
+/// Pushes `branch` to `remote` and sets it as the upstream.
+/// HTTPS remotes authenticate with `GITHUB_PERSONAL_TOKEN`, SSH remotes with the ssh-agent.
+pub fn push(repository: &Repository, remote: &str, branch: &str) -> Result<(), git2::Error> {
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|_url, username, allowed| {
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if let Ok(token) = env::var("GITHUB_PERSONAL_TOKEN") {
+                return Cred::userpass_plaintext("x-access-token", &token);
+            }
+        }
+        if allowed.contains(CredentialType::SSH_KEY) {
+            return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+        }
+        Cred::default()
+    });
+    callbacks.push_update_reference(|reference, status| match status {
+        Some(message) => Err(git2::Error::from_str(&format!(
+            "Couldn't push {}: {}",
+            reference, message
+        ))),
+        None => Ok(()),
+    });
+
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+
+    let refspec = format!("refs/heads/{}:refs/heads/{}", branch, branch);
+    repository
+        .find_remote(remote)?
+        .push(&[refspec], Some(&mut options))?;
+
+    repository
+        .find_branch(branch, BranchType::Local)?
+        .set_upstream(Some(&format!("{}/{}", remote, branch)))
+}
+
+fn relative_path(workdir: &Path, path: &Path) -> Result<PathBuf, git2::Error> {
+    let to_error = |error: std::io::Error| git2::Error::from_str(&error.to_string());
+    let workdir = workdir.canonicalize().map_err(to_error)?;
+    let path = path.canonicalize().map_err(to_error)?;
+
+    path.strip_prefix(&workdir)
+        .map(|path| path.to_path_buf())
+        .map_err(|_| {
+            git2::Error::from_str(&format!(
+                "{} is outside of the repository",
+                path.to_string_lossy()
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn repository_helper() -> (TempDir, Repository, TempDir, Repository) {
+        let origin_dir = TempDir::new().unwrap();
+        let origin = Repository::init_bare(origin_dir.path()).unwrap();
+
+        let work_dir = TempDir::new().unwrap();
+        let repository = Repository::init(work_dir.path()).unwrap();
+        repository
+            .remote("origin", origin_dir.path().to_str().unwrap())
+            .unwrap();
+
+        fs::write(work_dir.path().join("sample_config.json"), "{}").unwrap();
+        commit_initial(&repository);
+
+        (origin_dir, origin, work_dir, repository)
+    }
+
+    fn commit_initial(repository: &Repository) {
+        let mut index = repository.index().unwrap();
+        index.add_path(Path::new("sample_config.json")).unwrap();
+        index.write().unwrap();
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        repository
+            .commit(Some("HEAD"), &signature, &signature, "Initial", &tree, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn it_creates_branch_name() {
+        assert_eq!("twios-2023-09-18", branch_name("twios/2023-09-18.md"));
+        assert_eq!("twios-2023-09-18", branch_name("2023-09-18.md"));
+    }
+
+    #[test]
+    fn it_parses_github_remotes() {
+        let expected = Some(("mainmatter".to_string(), "twios".to_string()));
+        assert_eq!(
+            expected,
+            github_repository("git@github.com:mainmatter/twios.git")
+        );
+        assert_eq!(
+            expected,
+            github_repository("https://github.com/mainmatter/twios")
+        );
+        assert_eq!(
+            expected,
+            github_repository("https://x-access-token@github.com/mainmatter/twios.git")
+        );
+        assert_eq!(None, github_repository("/tmp/origin"));
+    }
+
+    #[test]
+    fn it_commits_and_pushes_post() {
+        let (_origin_dir, origin, work_dir, repository) = repository_helper();
+        let post = work_dir.path().join("twios/2023-09-18.md");
+        let json = work_dir.path().join("twios/2023-09-18.json");
+        let config = work_dir.path().join("sample_config.json");
+        fs::create_dir_all(post.parent().unwrap()).unwrap();
+        fs::write(&post, "## Rust").unwrap();
+        fs::write(&json, "[]").unwrap();
+        fs::write(&config, r#"{"labels": []}"#).unwrap();
+
+        let oid = commit(
+            &repository,
+            "twios-2023-09-18",
+            &[
+                post.to_string_lossy().to_string(),
+                json.to_string_lossy().to_string(),
+                config.to_string_lossy().to_string(),
+            ],
+            "TWIOS 2023-09-18",
+        )
+        .unwrap();
+        push(&repository, "origin", "twios-2023-09-18").unwrap();
+
+        assert_eq!(
+            Some("twios-2023-09-18"),
+            repository.head().unwrap().shorthand()
+        );
+
+        let pushed = origin
+            .find_branch("twios-2023-09-18", BranchType::Local)
+            .unwrap()
+            .get()
+            .peel_to_commit()
+            .unwrap();
+        assert_eq!(oid, pushed.id());
+        assert_eq!(Some("TWIOS 2023-09-18"), pushed.message());
+        assert_eq!(1, pushed.parent_count());
+
+        let tree = pushed.tree().unwrap();
+        assert!(tree.get_path(Path::new("twios/2023-09-18.md")).is_ok());
+        assert!(tree.get_path(Path::new("twios/2023-09-18.json")).is_ok());
+        let config_blob = tree
+            .get_path(Path::new("sample_config.json"))
+            .unwrap()
+            .to_object(&origin)
+            .unwrap();
+        assert_eq!(
+            r#"{"labels": []}"#.as_bytes(),
+            config_blob.as_blob().unwrap().content()
+        );
+    }
+
+    #[test]
+    fn it_refuses_to_reuse_existing_branch() {
+        let (_origin_dir, _origin, work_dir, repository) = repository_helper();
+        let head = repository.head().unwrap().peel_to_commit().unwrap();
+        repository.branch("twios-2023-09-18", &head, false).unwrap();
+        let config = work_dir.path().join("sample_config.json");
+
+        assert!(commit(
+            &repository,
+            "twios-2023-09-18",
+            &[config.to_string_lossy().to_string()],
+            "TWIOS 2023-09-18",
+        )
+        .is_err());
+    }
+}
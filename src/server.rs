@@ -0,0 +1,220 @@
+use hmac::{Hmac, Mac};
+use log::warn;
+use serde::Deserialize;
+use sha2::Sha256;
+use tiny_http::{Request, Response};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+#[derive(Debug, PartialEq)]
+pub enum WebhookError {
+    MissingSignature,
+    InvalidSignature,
+    InvalidPayload(String),
+}
+
+impl WebhookError {
+    pub fn status_code(&self) -> u16 {
+        match self {
+            WebhookError::MissingSignature | WebhookError::InvalidSignature => 401,
+            WebhookError::InvalidPayload(_) => 400,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct WebhookRequest {
+    pub event: String,
+    pub signature: Option<String>,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct Body {
+    body: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PullRequestPayload {
+    action: String,
+    pull_request: Body,
+}
+
+#[derive(Deserialize)]
+struct IssueCommentPayload {
+    action: String,
+    comment: Body,
+}
+
+impl WebhookRequest {
+    pub fn read(request: &mut Request) -> std::io::Result<Self> {
+        let event = read_header(request, "X-GitHub-Event").unwrap_or_default();
+        let signature = read_header(request, "X-Hub-Signature-256");
+
+        let mut payload = vec![];
+        request.as_reader().read_to_end(&mut payload)?;
+
+        Ok(WebhookRequest {
+            event,
+            signature,
+            payload,
+        })
+    }
+
+    pub fn verify(&self, secret: &str) -> Result<(), WebhookError> {
+        let signature = self
+            .signature
+            .as_ref()
+            .ok_or(WebhookError::MissingSignature)?;
+        verify_signature(secret, &self.payload, signature)
+    }
+
+    /// Returns the body of a TWIOS review comment carried by the webhook.
+    /// `None` means the event is irrelevant and should be acknowledged without changes.
+    pub fn comment_body(&self) -> Result<Option<String>, WebhookError> {
+        let invalid_payload =
+            |error: serde_json::Error| WebhookError::InvalidPayload(error.to_string());
+
+        let body = match self.event.as_str() {
+            "pull_request" => {
+                let payload: PullRequestPayload =
+                    serde_json::from_slice(&self.payload).map_err(invalid_payload)?;
+                match payload.action.as_str() {
+                    "opened" | "edited" | "labeled" | "unlabeled" => payload.pull_request.body,
+                    _ => None,
+                }
+            }
+            "issue_comment" => {
+                let payload: IssueCommentPayload =
+                    serde_json::from_slice(&self.payload).map_err(invalid_payload)?;
+                match payload.action.as_str() {
+                    "created" | "edited" => payload.comment.body,
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        Ok(body.filter(|body| body.contains("TWIOS_")))
+    }
+}
+
+fn read_header(request: &Request, name: &'static str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.to_string())
+}
+
+pub fn verify_signature(secret: &str, payload: &[u8], signature: &str) -> Result<(), WebhookError> {
+    let signature = signature
+        .strip_prefix(SIGNATURE_PREFIX)
+        .and_then(|signature| hex::decode(signature).ok())
+        .ok_or(WebhookError::InvalidSignature)?;
+
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(payload);
+    mac.verify_slice(&signature)
+        .map_err(|_| WebhookError::InvalidSignature)
+}
+
+pub fn respond(request: Request, status_code: u16, message: &str) {
+    let response = Response::from_string(message).with_status_code(status_code);
+    if let Err(error) = request.respond(response) {
+        warn!("Could not respond to webhook: {:?}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "It's a Secret to Everybody";
+    const PULL_REQUEST_PAYLOAD: &str =
+        include_str!("../fixtures/webhooks/pull_request_edited.json");
+    const ISSUE_COMMENT_PAYLOAD: &str =
+        include_str!("../fixtures/webhooks/issue_comment_created.json");
+
+    fn sign(payload: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(payload);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    fn request_helper(event: &str, payload: &str) -> WebhookRequest {
+        WebhookRequest {
+            event: event.to_string(),
+            signature: Some(sign(payload.as_bytes())),
+            payload: payload.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn it_verifies_github_example_signature() {
+        // https://docs.github.com/en/webhooks/using-webhooks/validating-webhook-deliveries#testing-the-webhook-payload-validation
+        assert_eq!(
+            Ok(()),
+            verify_signature(
+                SECRET,
+                b"Hello, World!",
+                "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"
+            )
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_signatures() {
+        let mut request = request_helper("pull_request", PULL_REQUEST_PAYLOAD);
+        assert_eq!(Err(WebhookError::InvalidSignature), request.verify("wrong"));
+
+        request.signature = Some("sha1=abc".to_string());
+        assert_eq!(Err(WebhookError::InvalidSignature), request.verify(SECRET));
+
+        request.signature = None;
+        assert_eq!(Err(WebhookError::MissingSignature), request.verify(SECRET));
+    }
+
+    #[test]
+    fn it_reads_pull_request_body() {
+        let request = request_helper("pull_request", PULL_REQUEST_PAYLOAD);
+        assert_eq!(Ok(()), request.verify(SECRET));
+
+        let body = request.comment_body().unwrap().unwrap();
+        assert!(body.contains("- TWIOS_DATE 2023-09-11..2023-09-18"));
+        assert!(body.contains("[EmbarkStudios/spdx] Rust @BobrImperator"));
+    }
+
+    #[test]
+    fn it_reads_issue_comment_body() {
+        let request = request_helper("issue_comment", ISSUE_COMMENT_PAYLOAD);
+        assert_eq!(Ok(()), request.verify(SECRET));
+
+        let body = request.comment_body().unwrap().unwrap();
+        assert!(body.contains("[simplabs/ember-error-route] EXCLUDED @mansona"));
+    }
+
+    #[test]
+    fn it_ignores_unrelated_events() {
+        let request = request_helper("push", PULL_REQUEST_PAYLOAD);
+        assert_eq!(Ok(None), request.comment_body());
+
+        let request = request_helper(
+            "pull_request",
+            &PULL_REQUEST_PAYLOAD.replace(r#""action": "edited""#, r#""action": "closed""#),
+        );
+        assert_eq!(Ok(None), request.comment_body());
+    }
+
+    #[test]
+    fn it_rejects_malformed_payloads() {
+        let request = request_helper("pull_request", "{}");
+        assert!(matches!(
+            request.comment_body(),
+            Err(WebhookError::InvalidPayload(_))
+        ));
+    }
+}
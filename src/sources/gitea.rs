@@ -0,0 +1,373 @@
+use super::{encode, http, trim_base_url, Search, SourceError};
+use crate::cli::PullRequestQueryType;
+use crate::{Item, ItemMergeStatus};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+
+const DEFAULT_BASE_URL: &str = "https://codeberg.org";
+const DEFAULT_PREFIX: &str = "codeberg";
+const DEFAULT_TOKEN_ENV: &str = "GITEA_TOKEN";
+const PER_PAGE: usize = 50;
+/// PRs opened this long before the period are still found when they're merged in it.
+const FEED_LOOKBACK_DAYS: i64 = 90;
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GiteaConfig {
+    /// Defaults to `https://codeberg.org`, any Gitea or Forgejo instance works.
+    #[serde(default)]
+    pub base_url: String,
+    /// Prefix of the repository names, defaults to `codeberg`, e.g. `codeberg:forgejo/forgejo`.
+    #[serde(default)]
+    pub name: String,
+    /// Handle on the instance by the login of the person in `users`.
+    pub users: BTreeMap<String, String>,
+    /// Variable holding an access token, defaults to `GITEA_TOKEN`.
+    #[serde(default)]
+    pub token_env: String,
+}
+
+impl GiteaConfig {
+    pub fn prefix(&self) -> String {
+        if self.name.is_empty() {
+            DEFAULT_PREFIX.to_string()
+        } else {
+            self.name.clone()
+        }
+    }
+
+    fn base_url(&self) -> &str {
+        if self.base_url.is_empty() {
+            DEFAULT_BASE_URL
+        } else {
+            trim_base_url(&self.base_url)
+        }
+    }
+
+    fn token(&self) -> Option<String> {
+        let token_env = if self.token_env.is_empty() {
+            DEFAULT_TOKEN_ENV
+        } else {
+            &self.token_env
+        };
+        env::var(token_env).ok()
+    }
+
+    /// Newest first, only what `handle` did themselves.
+    fn feed_url(&self, handle: &str, page: usize) -> String {
+        format!(
+            "{}/api/v1/users/{}/activities/feeds?only-performed-by=true&limit={}&page={}",
+            self.base_url(),
+            encode(handle),
+            PER_PAGE,
+            page
+        )
+    }
+
+    /// Pull requests opened by `handle` in `repo`. `since` applies to the last update,
+    /// PRs created or merged in the period are updated in it or later.
+    fn pulls_url(&self, repo: &str, handle: &str, search: &Search, page: usize) -> String {
+        let repo = repo.split('/').map(encode).collect::<Vec<_>>().join("/");
+        let mut url = format!(
+            "{}/api/v1/repos/{}/issues?type=pulls&state=all&created_by={}&limit={}&page={}",
+            self.base_url(),
+            repo,
+            encode(handle),
+            PER_PAGE,
+            page
+        );
+        if let Some(after) = search.after {
+            url.push_str(&format!(
+                "&since={}",
+                encode(&after.to_rfc3339_opts(SecondsFormat::Secs, true))
+            ));
+        }
+        url
+    }
+}
+
+#[derive(Deserialize)]
+struct ActivityRepository {
+    full_name: String,
+}
+
+#[derive(Deserialize)]
+struct Activity {
+    op_type: String,
+    created: DateTime<Utc>,
+    repo: Option<ActivityRepository>,
+}
+
+#[derive(Deserialize)]
+struct User {
+    login: String,
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Repository {
+    full_name: String,
+    owner: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct PullRequestMeta {
+    #[serde(default)]
+    merged: bool,
+    merged_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    draft: bool,
+}
+
+#[derive(Deserialize)]
+struct Issue {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    html_url: String,
+    user: User,
+    #[serde(default)]
+    labels: Vec<Label>,
+    state: String, // "open", "closed"
+    #[serde(default)]
+    comments: u32,
+    created_at: DateTime<Utc>,
+    closed_at: Option<DateTime<Utc>>,
+    pull_request: Option<PullRequestMeta>,
+    repository: Repository,
+}
+
+fn to_item(prefix: &str, issue: Issue) -> Item {
+    let pull_request = issue.pull_request.unwrap_or(PullRequestMeta {
+        merged: false,
+        merged_at: None,
+        draft: false,
+    });
+    let repository_url = issue
+        .html_url
+        .rsplit_once("/pulls/")
+        .map(|(repository_url, _)| repository_url.to_string())
+        .unwrap_or_default();
+
+    Item {
+        issue_number: issue.number.to_string(),
+        issue_title: issue.title,
+        issue_url: issue.html_url,
+        organization_name: issue.repository.owner,
+        repository_name: issue.repository.name,
+        full_repository_name: format!("{}:{}", prefix, issue.repository.full_name),
+        repository_url,
+        user_login: issue.user.login,
+        user_url: issue.user.html_url,
+        user_name: None,
+        state: issue.state,
+        merge_status: if pull_request.merged {
+            ItemMergeStatus::Merged
+        } else {
+            ItemMergeStatus::NotMerged
+        },
+        created_at: issue.created_at,
+        closed_at: issue.closed_at,
+        merged_at: pull_request.merged_at,
+        labels: issue.labels.into_iter().map(|label| label.name).collect(),
+        comments: issue.comments,
+        body: issue.body,
+        draft: pull_request.draft,
+        additions: None,
+        deletions: None,
+        changed_files: None,
+        bot: false,
+    }
+}
+
+/// Repos `handle` opened pull requests in, according to their activity feed.
+/// The feed is read back to the start of the period, or `FEED_LOOKBACK_DAYS` before it
+/// for PRs merged in the period.
+async fn contributed_repos(
+    config: &GiteaConfig,
+    handle: &str,
+    search: &Search,
+    headers: &[(&str, String)],
+) -> Result<Vec<String>, SourceError> {
+    let oldest = search.after.map(|after| match search.query_type {
+        PullRequestQueryType::Created => after,
+        _ => after - Duration::days(FEED_LOOKBACK_DAYS),
+    });
+    let mut repos: Vec<String> = vec![];
+
+    for page in 1.. {
+        let activities: Vec<Activity> =
+            http::get_json(&config.feed_url(handle, page), headers).await?;
+        let is_last_page = activities.len() < PER_PAGE;
+        let is_past_period = activities.last().map_or(true, |activity| {
+            oldest.is_some_and(|oldest| activity.created < oldest)
+        });
+
+        for activity in activities {
+            if activity.op_type != "create_pull_request" {
+                continue;
+            }
+            if let Some(repo) = activity.repo {
+                if !repos.contains(&repo.full_name) {
+                    repos.push(repo.full_name);
+                }
+            }
+        }
+        if is_last_page || is_past_period {
+            break;
+        }
+    }
+
+    Ok(repos)
+}
+
+/// Pull requests by the mapped `users` on the Gitea instance, narrowed down to `search`.
+pub async fn fetch_items(config: &GiteaConfig, search: &Search) -> Result<Vec<Item>, SourceError> {
+    let headers = match config.token() {
+        Some(token) => vec![("Authorization", format!("token {}", token))],
+        None => vec![],
+    };
+    let prefix = config.prefix();
+    let mut items: Vec<Item> = vec![];
+
+    for handle in config.users.values() {
+        for repo in contributed_repos(config, handle, search, &headers).await? {
+            for page in 1.. {
+                let issues: Vec<Issue> =
+                    http::get_json(&config.pulls_url(&repo, handle, search, page), &headers)
+                        .await?;
+                let is_last_page = issues.len() < PER_PAGE;
+
+                for issue in issues {
+                    let merged_at = issue
+                        .pull_request
+                        .as_ref()
+                        .and_then(|pull_request| pull_request.merged_at);
+                    let is_duplicate = items.iter().any(|item| item.issue_url == issue.html_url);
+                    if search.matches(issue.created_at, merged_at) && !is_duplicate {
+                        items.push(to_item(&prefix, issue));
+                    }
+                }
+                if is_last_page {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::mock_server_responses;
+
+    const ACTIVITIES: &str = include_str!("../../fixtures/gitea/activities.json");
+    const PULL_REQUESTS: &str = include_str!("../../fixtures/gitea/pull_requests.json");
+    const COMMUNITY_PULL_REQUESTS: &str =
+        include_str!("../../fixtures/gitea/community_pull_requests.json");
+
+    fn config_helper(base_url: String) -> GiteaConfig {
+        GiteaConfig {
+            base_url,
+            name: String::new(),
+            users: BTreeMap::from([("BobrImperator".to_string(), "bobr".to_string())]),
+            token_env: "TWIOS_TEST_GITEA_TOKEN".to_string(),
+        }
+    }
+
+    fn search_helper(query_type: PullRequestQueryType) -> Search {
+        Search {
+            query_type,
+            after: Some("2023-09-11T00:00:00Z".parse().unwrap()),
+            before: Some("2023-09-18T00:00:00Z".parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn it_builds_urls() {
+        let config = config_helper(String::new());
+
+        assert_eq!(
+            "https://codeberg.org/api/v1/users/bobr/activities/feeds?only-performed-by=true&limit=50&page=2",
+            config.feed_url("bobr", 2)
+        );
+        assert_eq!(
+            "https://codeberg.org/api/v1/repos/forgejo/forgejo/issues?type=pulls&state=all&created_by=bobr&limit=50&page=1&since=2023-09-11T00%3A00%3A00Z",
+            config.pulls_url(
+                "forgejo/forgejo",
+                "bobr",
+                &search_helper(PullRequestQueryType::Created),
+                1
+            )
+        );
+        assert!(config
+            .pulls_url(
+                "some org/x&y",
+                "bobr",
+                &search_helper(PullRequestQueryType::Created),
+                1
+            )
+            .contains("/repos/some%20org/x%26y/issues?"));
+    }
+
+    #[tokio::test]
+    async fn it_fetches_pull_requests() {
+        let (base_url, requested_url) = mock_server_responses(vec![
+            (200, ACTIVITIES),
+            (200, COMMUNITY_PULL_REQUESTS),
+            (200, PULL_REQUESTS),
+        ]);
+
+        let items = fetch_items(
+            &config_helper(base_url),
+            &search_helper(PullRequestQueryType::Both),
+        )
+        .await
+        .unwrap();
+
+        assert!(requested_url
+            .recv()
+            .unwrap()
+            .starts_with("/api/v1/users/bobr/activities/feeds?only-performed-by=true"));
+        assert!(requested_url.recv().unwrap().starts_with(
+            "/api/v1/repos/Codeberg/Community/issues?type=pulls&state=all&created_by=bobr"
+        ));
+        assert!(requested_url.recv().unwrap().starts_with(
+            "/api/v1/repos/forgejo/forgejo/issues?type=pulls&state=all&created_by=bobr"
+        ));
+        // #1400 was opened before the period and isn't merged.
+        assert_eq!(
+            vec!["codeberg:Codeberg/Community", "codeberg:forgejo/forgejo"],
+            items
+                .iter()
+                .map(|item| item.full_repository_name.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let open = &items[0];
+        assert_eq!("open", open.state);
+        assert!(open.draft);
+        assert_eq!(ItemMergeStatus::NotMerged, open.merge_status);
+
+        let merged = &items[1];
+        assert_eq!("1523", merged.issue_number);
+        assert_eq!("bobr", merged.user_login);
+        assert_eq!(
+            "https://codeberg.org/forgejo/forgejo",
+            merged.repository_url
+        );
+        assert_eq!("forgejo", merged.organization_name);
+        assert_eq!(ItemMergeStatus::Merged, merged.merge_status);
+        assert_eq!(vec!["bug".to_string()], merged.labels);
+    }
+}
@@ -0,0 +1,286 @@
+use super::{encode, http, trim_base_url, Search, SourceError};
+use crate::cli::PullRequestQueryType;
+use crate::{Item, ItemMergeStatus};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+const DEFAULT_BASE_URL: &str = "https://gitlab.com";
+const DEFAULT_PREFIX: &str = "gitlab";
+const DEFAULT_TOKEN_ENV: &str = "GITLAB_TOKEN";
+const PER_PAGE: usize = 100;
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitLabConfig {
+    /// Defaults to `https://gitlab.com`, e.g. `https://gitlab.gnome.org` for a self-hosted instance.
+    #[serde(default)]
+    pub base_url: String,
+    /// Prefix of the repository names, defaults to `gitlab`, e.g. `gitlab:GNOME/gtk`.
+    #[serde(default)]
+    pub name: String,
+    pub users: Vec<String>,
+    /// Variable holding a personal access token with `read_api`, defaults to `GITLAB_TOKEN`.
+    #[serde(default)]
+    pub token_env: String,
+}
+
+impl GitLabConfig {
+    pub fn prefix(&self) -> String {
+        if self.name.is_empty() {
+            DEFAULT_PREFIX.to_string()
+        } else {
+            self.name.clone()
+        }
+    }
+
+    fn base_url(&self) -> &str {
+        if self.base_url.is_empty() {
+            DEFAULT_BASE_URL
+        } else {
+            trim_base_url(&self.base_url)
+        }
+    }
+
+    fn token(&self) -> Option<String> {
+        let token_env = if self.token_env.is_empty() {
+            DEFAULT_TOKEN_ENV
+        } else {
+            &self.token_env
+        };
+        env::var(token_env).ok()
+    }
+
+    fn merge_requests_url(&self, user: &str, search: &Search, page: usize) -> String {
+        let timestamp = |timestamp: DateTime<Utc>| {
+            encode(&timestamp.to_rfc3339_opts(SecondsFormat::Secs, true))
+        };
+
+        let mut url = format!(
+            "{}/api/v4/merge_requests?scope=all&author_username={}&per_page={}&page={}",
+            self.base_url(),
+            encode(user),
+            PER_PAGE,
+            page
+        );
+        // Whatever was created or merged in the period was updated in it as well.
+        if let Some(after) = search.after {
+            url.push_str(&format!("&updated_after={}", timestamp(after)));
+        }
+        if search.query_type == PullRequestQueryType::Created {
+            if let Some(after) = search.after {
+                url.push_str(&format!("&created_after={}", timestamp(after)));
+            }
+            if let Some(before) = search.before {
+                url.push_str(&format!("&created_before={}", timestamp(before)));
+            }
+        }
+        url
+    }
+}
+
+#[derive(Deserialize)]
+struct Author {
+    username: String,
+    web_url: String,
+}
+
+#[derive(Deserialize)]
+struct MergeRequest {
+    iid: u64,
+    title: String,
+    web_url: String,
+    state: String, // "opened", "closed", "locked", "merged"
+    created_at: DateTime<Utc>,
+    merged_at: Option<DateTime<Utc>>,
+    closed_at: Option<DateTime<Utc>>,
+    author: Author,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    user_notes_count: u32,
+    #[serde(default)]
+    draft: bool,
+    description: Option<String>,
+}
+
+/// `https://gitlab.gnome.org/GNOME/gtk/-/merge_requests/1` -> (`https://gitlab.gnome.org/GNOME/gtk`, `GNOME/gtk`)
+fn project(web_url: &str) -> Option<(String, String)> {
+    let (project_url, _) = web_url.split_once("/-/merge_requests/")?;
+    let (_, path) = project_url.split_once("://")?.1.split_once('/')?;
+    Some((project_url.to_string(), path.to_string()))
+}
+
+fn to_item(prefix: &str, merge_request: MergeRequest) -> Option<Item> {
+    let (repository_url, path) = project(&merge_request.web_url)?;
+    let organization_name = path.split('/').next().unwrap_or_default().to_string();
+    let repository_name = path.rsplit('/').next().unwrap_or_default().to_string();
+    let merge_status = if merge_request.state == "merged" {
+        ItemMergeStatus::Merged
+    } else {
+        ItemMergeStatus::NotMerged
+    };
+
+    Some(Item {
+        issue_number: merge_request.iid.to_string(),
+        issue_title: merge_request.title,
+        issue_url: merge_request.web_url,
+        organization_name,
+        repository_name,
+        full_repository_name: format!("{}:{}", prefix, path),
+        repository_url,
+        user_login: merge_request.author.username,
+        user_url: merge_request.author.web_url,
+        user_name: None,
+        state: if merge_request.state == "opened" {
+            "open".to_string()
+        } else {
+            "closed".to_string()
+        },
+        merge_status,
+        created_at: merge_request.created_at,
+        closed_at: merge_request.closed_at.or(merge_request.merged_at),
+        merged_at: merge_request.merged_at,
+        labels: merge_request.labels,
+        comments: merge_request.user_notes_count,
+        body: merge_request.description,
+        draft: merge_request.draft,
+        additions: None,
+        deletions: None,
+        changed_files: None,
+        bot: false,
+    })
+}
+
+/// Merge requests authored by `users` on the GitLab instance, narrowed down to `search`.
+pub async fn fetch_items(config: &GitLabConfig, search: &Search) -> Result<Vec<Item>, SourceError> {
+    let headers = match config.token() {
+        Some(token) => vec![("PRIVATE-TOKEN", token)],
+        None => vec![],
+    };
+    let prefix = config.prefix();
+    let mut items = vec![];
+
+    for user in config.users.iter() {
+        for page in 1.. {
+            let merge_requests: Vec<MergeRequest> =
+                http::get_json(&config.merge_requests_url(user, search, page), &headers).await?;
+            let is_last_page = merge_requests.len() < PER_PAGE;
+
+            items.extend(
+                merge_requests
+                    .into_iter()
+                    .filter(|merge_request| {
+                        search.matches(merge_request.created_at, merge_request.merged_at)
+                    })
+                    .filter_map(|merge_request| to_item(&prefix, merge_request)),
+            );
+            if is_last_page {
+                break;
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::mock_server;
+
+    const MERGE_REQUESTS: &str = include_str!("../../fixtures/gitlab/merge_requests.json");
+
+    fn search_helper(query_type: PullRequestQueryType) -> Search {
+        Search {
+            query_type,
+            after: Some("2023-09-11T00:00:00Z".parse().unwrap()),
+            before: Some("2023-09-18T00:00:00Z".parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn it_reads_project_from_url() {
+        assert_eq!(
+            Some((
+                "https://gitlab.gnome.org/GNOME/gtk".to_string(),
+                "GNOME/gtk".to_string()
+            )),
+            project("https://gitlab.gnome.org/GNOME/gtk/-/merge_requests/6420")
+        );
+        assert_eq!(
+            Some((
+                "https://gitlab.com/inkscape/extras/extensions".to_string(),
+                "inkscape/extras/extensions".to_string()
+            )),
+            project("https://gitlab.com/inkscape/extras/extensions/-/merge_requests/12")
+        );
+        assert_eq!(None, project("https://gitlab.com/inkscape/inkscape"));
+    }
+
+    #[test]
+    fn it_builds_merge_requests_url() {
+        let config: GitLabConfig = serde_json::from_value(serde_json::json!({
+            "base_url": "https://gitlab.gnome.org/",
+            "users": ["BobrImperator"]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            "https://gitlab.gnome.org/api/v4/merge_requests?scope=all&author_username=BobrImperator&per_page=100&page=1&updated_after=2023-09-11T00%3A00%3A00Z&created_after=2023-09-11T00%3A00%3A00Z&created_before=2023-09-18T00%3A00%3A00Z",
+            config.merge_requests_url("BobrImperator", &search_helper(PullRequestQueryType::Created), 1)
+        );
+        assert_eq!(
+            "https://gitlab.gnome.org/api/v4/merge_requests?scope=all&author_username=BobrImperator&per_page=100&page=2&updated_after=2023-09-11T00%3A00%3A00Z",
+            config.merge_requests_url("BobrImperator", &search_helper(PullRequestQueryType::Merged), 2)
+        );
+    }
+
+    #[tokio::test]
+    async fn it_fetches_merge_requests() {
+        let (base_url, requested_url) = mock_server(200, MERGE_REQUESTS);
+        let config = GitLabConfig {
+            base_url,
+            name: "gnome".to_string(),
+            users: vec!["BobrImperator".to_string()],
+            token_env: "TWIOS_TEST_GITLAB_TOKEN".to_string(),
+        };
+
+        let items = fetch_items(&config, &search_helper(PullRequestQueryType::Merged))
+            .await
+            .unwrap();
+
+        assert!(requested_url
+            .recv()
+            .unwrap()
+            .starts_with("/api/v4/merge_requests?scope=all&author_username=BobrImperator"));
+        assert_eq!(1, items.len());
+
+        let item = &items[0];
+        assert_eq!("6420", item.issue_number);
+        assert_eq!("gnome:GNOME/gtk", item.full_repository_name);
+        assert_eq!("GNOME", item.organization_name);
+        assert_eq!("gtk", item.repository_name);
+        assert_eq!("https://gitlab.gnome.org/GNOME/gtk", item.repository_url);
+        assert_eq!("closed", item.state);
+        assert_eq!(ItemMergeStatus::Merged, item.merge_status);
+        assert_eq!(vec!["4.12".to_string()], item.labels);
+        assert_eq!(3, item.comments);
+    }
+
+    #[tokio::test]
+    async fn it_reports_failed_requests() {
+        let (base_url, _) = mock_server(401, "401 Unauthorized");
+        let config = GitLabConfig {
+            base_url,
+            name: String::new(),
+            users: vec!["BobrImperator".to_string()],
+            token_env: "TWIOS_TEST_GITLAB_TOKEN".to_string(),
+        };
+
+        let error = fetch_items(&config, &search_helper(PullRequestQueryType::Merged))
+            .await
+            .unwrap_err();
+        assert!(error.0.contains("401"));
+    }
+}
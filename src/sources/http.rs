@@ -0,0 +1,47 @@
+use super::SourceError;
+use hyper::{body, Body, Client, Request};
+use hyper_tls::HttpsConnector;
+use log::debug;
+use serde::de::DeserializeOwned;
+
+const USER_AGENT: &str = concat!("this-week-in-open-source/", env!("CARGO_PKG_VERSION"));
+
+/// GETs `url` and parses the JSON response, any non-2xx status is an error.
+pub async fn get_json<T: DeserializeOwned>(
+    url: &str,
+    headers: &[(&str, String)],
+) -> Result<T, SourceError> {
+    debug!("Fetching {}", url);
+    let client = Client::builder().build::<_, Body>(HttpsConnector::new());
+
+    let mut request = Request::get(url)
+        .header("User-Agent", USER_AGENT)
+        .header("Accept", "application/json");
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+    let request = request
+        .body(Body::empty())
+        .map_err(|error| SourceError(format!("Invalid request to {}: {}", url, error)))?;
+
+    let response = client
+        .request(request)
+        .await
+        .map_err(|error| SourceError(format!("Couldn't reach {}: {}", url, error)))?;
+    let status = response.status();
+    let bytes = body::to_bytes(response.into_body())
+        .await
+        .map_err(|error| SourceError(format!("Couldn't read {}: {}", url, error)))?;
+
+    if !status.is_success() {
+        return Err(SourceError(format!(
+            "{} responded with {}: {}",
+            url,
+            status,
+            String::from_utf8_lossy(&bytes)
+        )));
+    }
+
+    serde_json::from_slice(&bytes)
+        .map_err(|error| SourceError(format!("Unexpected response from {}: {}", url, error)))
+}
@@ -0,0 +1,154 @@
+use crate::cli::PullRequestQueryType;
+use crate::Item;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub mod gitea;
+pub mod gitlab;
+mod http;
+
+/// Code hosts searched in addition to GitHub, configured in `sources`.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum SourceConfig {
+    GitLab(gitlab::GitLabConfig),
+    Gitea(gitea::GiteaConfig),
+}
+
+impl SourceConfig {
+    pub fn name(&self) -> String {
+        match self {
+            SourceConfig::GitLab(config) => config.prefix(),
+            SourceConfig::Gitea(config) => config.prefix(),
+        }
+    }
+
+    pub async fn fetch_items(&self, search: &Search) -> Result<Vec<Item>, SourceError> {
+        match self {
+            SourceConfig::GitLab(config) => gitlab::fetch_items(config, search).await,
+            SourceConfig::Gitea(config) => gitea::fetch_items(config, search).await,
+        }
+    }
+}
+
+/// The part of `AppParams` a source needs, with `date` resolved to `[after, before)`.
+#[derive(Debug, Clone)]
+pub struct Search {
+    pub query_type: PullRequestQueryType,
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+}
+
+impl Search {
+    pub fn includes(&self, timestamp: DateTime<Utc>) -> bool {
+        self.after.map_or(true, |after| after <= timestamp)
+            && self.before.map_or(true, |before| timestamp < before)
+    }
+
+    /// Applies `query_type` to the timestamps of a pull request.
+    pub fn matches(&self, created_at: DateTime<Utc>, merged_at: Option<DateTime<Utc>>) -> bool {
+        let is_created = self.includes(created_at);
+        let is_merged = merged_at.is_some_and(|merged_at| self.includes(merged_at));
+
+        match self.query_type {
+            PullRequestQueryType::Created => is_created,
+            PullRequestQueryType::Merged => is_merged,
+            PullRequestQueryType::Both => is_created || is_merged,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct SourceError(pub String);
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// `https://gitlab.com/` -> `https://gitlab.com`
+fn trim_base_url(base_url: &str) -> &str {
+    base_url.trim_end_matches('/')
+}
+
+/// Percent-encodes a query parameter, only unreserved characters are kept as they are.
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Serves `body` with `status_code` for a single request and reports the requested url.
+#[cfg(test)]
+pub fn mock_server(
+    status_code: u16,
+    body: &'static str,
+) -> (String, std::sync::mpsc::Receiver<String>) {
+    mock_server_responses(vec![(status_code, body)])
+}
+
+/// Serves one of `responses` per request in turn and reports every requested url.
+#[cfg(test)]
+pub fn mock_server_responses(
+    responses: Vec<(u16, &'static str)>,
+) -> (String, std::sync::mpsc::Receiver<String>) {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let port = server.server_addr().to_ip().unwrap().port();
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || {
+        for (status_code, body) in responses {
+            let request = server.recv().unwrap();
+            let _ = sender.send(request.url().to_string());
+            request
+                .respond(tiny_http::Response::from_string(body).with_status_code(status_code))
+                .unwrap();
+        }
+    });
+
+    (format!("http://127.0.0.1:{}/", port), receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_matches_search() {
+        let timestamp = |value: &str| value.parse::<DateTime<Utc>>().unwrap();
+        let search = Search {
+            query_type: PullRequestQueryType::Merged,
+            after: Some(timestamp("2023-09-11T00:00:00Z")),
+            before: Some(timestamp("2023-09-18T00:00:00Z")),
+        };
+
+        assert!(search.includes(timestamp("2023-09-11T00:00:00Z")));
+        assert!(!search.includes(timestamp("2023-09-18T00:00:00Z")));
+        assert!(search.matches(
+            timestamp("2023-09-01T10:00:00Z"),
+            Some(timestamp("2023-09-12T10:00:00Z"))
+        ));
+        assert!(!search.matches(timestamp("2023-09-12T10:00:00Z"), None));
+
+        let search = Search {
+            query_type: PullRequestQueryType::Both,
+            ..search
+        };
+        assert!(search.matches(timestamp("2023-09-12T10:00:00Z"), None));
+    }
+
+    #[test]
+    fn it_encodes_query_parameters() {
+        assert_eq!("forgejo", encode("forgejo"));
+        assert_eq!("a%26b%3Dc%20d", encode("a&b=c d"));
+    }
+}